@@ -0,0 +1,102 @@
+//! Time and memory needed to compare two large, nearly identical documents.
+//!
+//! Run with `cargo bench --bench large_documents`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use json_diff_rs::DiffBuilder;
+use serde_json::{json, Value};
+
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn document(items: usize, changed_every: usize) -> Value {
+    let items: Vec<_> = (0..items)
+        .map(|i| {
+            let version = if changed_every > 0 && i % changed_every == 0 { 2 } else { 1 };
+            json!({
+                "id": i,
+                "name": format!("item {i}"),
+                "version": version,
+                "price": i as f64 * 0.25,
+                "tags": ["a", "b", "c"],
+                "attributes": {"color": "red", "size": i % 10, "nested": {"depth": [1, 2, 3]}},
+            })
+        })
+        .collect();
+    json!({ "items": items })
+}
+
+/// Time, allocated bytes and peak memory of one step of a benchmark, summed over its iterations.
+#[derive(Default)]
+struct Measurement {
+    elapsed: Duration,
+    allocated: usize,
+    peak: usize,
+}
+
+impl Measurement {
+    fn measure<T>(&mut self, f: impl FnOnce() -> T) -> T {
+        let (allocated_before, current) = (ALLOCATED.load(Ordering::Relaxed), CURRENT.load(Ordering::Relaxed));
+        PEAK.store(current, Ordering::Relaxed);
+        let start = Instant::now();
+        let result = black_box(f());
+        self.elapsed += start.elapsed();
+        self.allocated += ALLOCATED.load(Ordering::Relaxed) - allocated_before;
+        self.peak = self.peak.max(PEAK.load(Ordering::Relaxed) - current);
+        result
+    }
+
+    fn print(&self, name: &str, iterations: u32) {
+        println!(
+            "{name:<36} {:>10.2?}/iter {:>10.2} MiB allocated/iter {:>10.2} MiB peak",
+            self.elapsed / iterations,
+            self.allocated as f64 / iterations as f64 / (1024.0 * 1024.0),
+            self.peak as f64 / (1024.0 * 1024.0),
+        );
+    }
+}
+
+fn bench(name: &str, items: usize, changed_every: usize) {
+    let iterations = 5;
+    let mut build = Measurement::default();
+    let mut compare = Measurement::default();
+
+    for _ in 0..iterations {
+        let (source, target) = (document(items, 0), document(items, changed_every));
+        let diff = build.measure(|| DiffBuilder::default().source(source).target(target).build().unwrap());
+        compare.measure(|| diff.compare().is_some());
+    }
+
+    build.print(&format!("{name}, build"), iterations);
+    compare.print(&format!("{name}, compare"), iterations);
+}
+
+fn main() {
+    bench("100k items, equal", 100_000, 0);
+    bench("100k items, 1% changed", 100_000, 100);
+    bench("100k items, all changed", 100_000, 1);
+}
@@ -0,0 +1,108 @@
+use crate::{ColorChoice, DiffBuilder};
+
+/// Asserts that two serializable values are equal as JSON, and panics with a path-by-path report
+/// of their differences otherwise.
+///
+/// Any [`DiffBuilder`] option can follow the values, written as a method call:
+///
+/// ```
+/// # use json_diff_rs::assert_json_eq;
+/// # use serde_json::json;
+/// assert_json_eq!(
+///     json!({"name": "John", "updated_at": 1, "price": 1.001}),
+///     json!({"name": "John", "updated_at": 2, "price": 1.0}),
+///     ignore_path("updated_at"),
+///     approx_float_eq_epsilon(0.01),
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_json_eq {
+    ($actual:expr, $expected:expr $(, $option:ident($($arg:expr),* $(,)?))* $(,)?) => {{
+        let mut builder = $crate::DiffBuilder::default();
+        $(builder.$option($($arg),*);)*
+        builder.source_serializable(&$actual).target_serializable(&$expected);
+        $crate::__assert_json(&builder, "assert_json_eq");
+    }};
+}
+
+/// Like [`assert_json_eq!`], but `actual` may have object members and array elements that
+/// `expected` doesn't, see [`CompareMode::Contains`](crate::CompareMode::Contains).
+///
+/// ```
+/// # use json_diff_rs::assert_json_include;
+/// # use serde_json::json;
+/// assert_json_include!(
+///     json!({"name": "John", "address": {"city": "Astana", "zip": 123}, "tags": ["c", "a", "b"]}),
+///     json!({"address": {"city": "Astana"}, "tags": ["b", "a"]}),
+///     unordered_array("tags"),
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_json_include {
+    ($actual:expr, $expected:expr $(, $option:ident($($arg:expr),* $(,)?))* $(,)?) => {{
+        let mut builder = $crate::DiffBuilder::default();
+        builder.compare_mode($crate::CompareMode::Contains);
+        $(builder.$option($($arg),*);)*
+        builder.source_serializable(&$actual).target_serializable(&$expected);
+        $crate::__assert_json(&builder, "assert_json_include");
+    }};
+}
+
+#[doc(hidden)]
+#[track_caller]
+pub fn assert_json(builder: &DiffBuilder, name: &str) {
+    let diff = match builder.build() {
+        Ok(diff) => diff,
+        Err(err) => panic!("{name}!: {err}"),
+    };
+
+    if let Some(difference) = diff.compare() {
+        panic!("{name}! failed (- actual, + expected):\n{}", difference.render(ColorChoice::Never));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    #[test]
+    fn test_assert_json_eq() {
+        assert_json_eq!(json!({"a": [1, 2]}), json!({"a": [1, 2]}));
+        assert_json_eq!(
+            json!({"items": [{"id": 2, "v": 1.001}, {"id": 1, "v": 1}], "at": 1}),
+            json!({"items": [{"id": 1, "v": 1}, {"id": 2, "v": 1.0}], "at": 2}),
+            array_key("items", "id"),
+            approx_float_eq_epsilon(0.01),
+            ignore_path("at"),
+        );
+
+        let panic = std::panic::catch_unwind(|| {
+            assert_json_eq!(json!({"name": "John", "tags": ["a"]}), json!({"name": "Joe", "tags": ["a"]}));
+        })
+        .unwrap_err();
+        assert_eq!(
+            panic.downcast_ref::<String>().unwrap(),
+            "assert_json_eq! failed (- actual, + expected):\nname: - \"John\" / + \"Joe\"\n",
+        );
+
+        let panic = std::panic::catch_unwind(|| assert_json_eq!(json!(1), json!(1), ignore_path("a.[x]"))).unwrap_err();
+        assert!(panic.downcast_ref::<String>().unwrap().starts_with("assert_json_eq!: invalid path `a.[x]`"));
+    }
+
+    #[test]
+    fn test_assert_json_include() {
+        assert_json_include!(
+            json!({"name": "John", "address": {"city": "Astana", "zip": 123}, "items": [{"id": 1, "extra": true}]}),
+            json!({"address": {"city": "Astana"}, "items": [{"id": 1}]}),
+        );
+
+        let panic = std::panic::catch_unwind(|| {
+            assert_json_include!(json!({"name": "John", "extra": 1}), json!({"name": "Joe", "missing": 2}));
+        })
+        .unwrap_err();
+        assert_eq!(
+            panic.downcast_ref::<String>().unwrap(),
+            "assert_json_include! failed (- actual, + expected):\nname: - \"John\" / + \"Joe\"\nmissing: + 2\n",
+        );
+    }
+}
@@ -0,0 +1,373 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::{Parser, ValueEnum};
+use json_diff_rs::{ArrayDiffMode, ColorChoice, CompareMode, DiffBuilder, MergePatchError, TextGranularity, Tolerance};
+use thiserror::Error;
+
+/// Compare two JSON documents and print the difference.
+///
+/// Exits with 0 when the documents are equal, 1 when they differ and 2 on error.
+#[derive(Debug, Parser)]
+#[command(name = "json-diff", version)]
+struct Args {
+    /// Source JSON file, or `-` to read it from stdin
+    source: String,
+
+    /// Target JSON file, or `-` to read it from stdin
+    target: String,
+
+    /// Ignore the value at PATH, e.g. `address.city`, `items.[_].id`, `**.updated_at`, a JSON
+    /// Pointer such as `/items/0/id` or a JSONPath expression such as `$.items[?@.internal]`
+    #[arg(long = "ignore", value_name = "PATH")]
+    ignore: Vec<String>,
+
+    /// Ignore the value at PATH, even if it is missing in the target
+    #[arg(long = "ignore-missing", value_name = "PATH")]
+    ignore_missing: Vec<String>,
+
+    /// Treat empty arrays and nulls as equal
+    #[arg(long)]
+    equate_empty_arrays: bool,
+
+    /// Maximum difference between two numbers, at least one of them a float, that are still considered equal
+    #[arg(long, value_name = "EPSILON", default_value_t = 0.0)]
+    float_epsilon: f64,
+
+    /// Treat integers and floats with the same value, e.g. `1` and `1.0`, as different
+    #[arg(long)]
+    strict_numbers: bool,
+
+    /// Compare the numbers at PATH with their own tolerance: absolute, e.g. `prices.[_]=0.01`,
+    /// relative, e.g. `metrics.*=1%`, or in units in the last place, e.g. `ratio=4ulp`
+    #[arg(long = "tolerance", value_name = "PATH=TOLERANCE", value_parser = parse_tolerance)]
+    tolerances: Vec<(String, Tolerance)>,
+
+    /// Maximum difference between two RFC 3339 date-times that are still considered equal,
+    /// e.g. `500ms`, `2s`, `1m` or `1h`
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    datetime_tolerance: Option<Duration>,
+
+    /// Pair the elements of the array at PATH by their KEY fields instead of their index,
+    /// e.g. `items=id` or `items.[_]=kind,name`
+    #[arg(long = "array-key", value_name = "PATH=KEY[,KEY]", value_parser = parse_array_key)]
+    array_keys: Vec<(String, Vec<String>)>,
+
+    /// Compare the array at PATH regardless of the order of its elements
+    #[arg(long = "unordered", value_name = "PATH")]
+    unordered: Vec<String>,
+
+    /// How array elements are paired up for comparison
+    #[arg(long, value_enum, default_value_t = ArrayMode::Index)]
+    array_diff: ArrayMode,
+
+    /// Whether one document only has to contain the other
+    #[arg(long, value_enum, default_value_t = Mode::Exact)]
+    mode: Mode,
+
+    /// Show changed strings as an inline diff of their characters, words or lines in the text
+    /// output
+    #[arg(long, value_enum, value_name = "UNIT")]
+    text_diff: Option<TextUnit>,
+
+    /// Strings shorter than this many characters are shown whole, without an inline diff
+    #[arg(long, value_name = "CHARS", default_value_t = 40)]
+    text_diff_min_length: usize,
+
+    /// What to print when the documents differ
+    #[arg(long, value_enum, default_value_t = Output::Difference)]
+    output: Output,
+
+    /// Number of unchanged lines around each change in the unified output
+    #[arg(long, value_name = "LINES", default_value_t = 3)]
+    context: usize,
+
+    /// Width of each column in the side-by-side output
+    #[arg(long, value_name = "COLUMNS", default_value_t = 60)]
+    width: usize,
+
+    /// When to color the text, unified and side-by-side output
+    #[arg(long, value_enum, default_value_t = Color::Auto)]
+    color: Color,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+impl From<Color> for ColorChoice {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Auto => ColorChoice::Auto,
+            Color::Always => ColorChoice::Always,
+            Color::Never => ColorChoice::Never,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Output {
+    /// One `path: - old / + new` line per change
+    Text,
+    /// A unified diff of the pretty-printed documents
+    Unified,
+    /// The pretty-printed documents side by side
+    SideBySide,
+    /// The structural difference as JSON
+    Difference,
+    /// An RFC 6902 JSON Patch that turns the source into the target
+    JsonPatch,
+    /// An RFC 7386 JSON Merge Patch that turns the source into the target
+    MergePatch,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ArrayMode {
+    /// Compare elements index by index
+    Index,
+    /// Align elements by their longest common subsequence
+    Lcs,
+    /// Compare elements regardless of their order
+    Unordered,
+}
+
+impl From<ArrayMode> for ArrayDiffMode {
+    fn from(mode: ArrayMode) -> Self {
+        match mode {
+            ArrayMode::Index => ArrayDiffMode::Index,
+            ArrayMode::Lcs => ArrayDiffMode::Lcs,
+            ArrayMode::Unordered => ArrayDiffMode::Unordered,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum TextUnit {
+    Chars,
+    Words,
+    Lines,
+}
+
+impl From<TextUnit> for TextGranularity {
+    fn from(unit: TextUnit) -> Self {
+        match unit {
+            TextUnit::Chars => TextGranularity::Chars,
+            TextUnit::Words => TextGranularity::Words,
+            TextUnit::Lines => TextGranularity::Lines,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Mode {
+    /// Both documents must be equal
+    Exact,
+    /// The source must contain the target, extra members and elements are allowed
+    Contains,
+    /// The target must contain the source, extra members and elements are allowed
+    ContainedIn,
+}
+
+impl From<Mode> for CompareMode {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Exact => CompareMode::Exact,
+            Mode::Contains => CompareMode::Contains,
+            Mode::ContainedIn => CompareMode::ContainedIn,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+enum CliError {
+    #[error("source and target cannot both be read from stdin")]
+    StdinTwice,
+    #[error("failed to read {path}: {source}")]
+    Read { path: String, source: io::Error },
+    #[error("failed to parse {path}: {source}")]
+    Parse { path: String, source: serde_json::Error },
+    #[error(transparent)]
+    Build(#[from] json_diff_rs::Error),
+    #[error(transparent)]
+    MergePatch(#[from] MergePatchError),
+    #[error("failed to serialize the difference: {0}")]
+    Serialize(#[from] serde_json::Error),
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(err) => {
+            eprintln!("json-diff: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Returns whether the documents are equal.
+fn run(args: Args) -> Result<bool, CliError> {
+    if args.source == "-" && args.target == "-" {
+        return Err(CliError::StdinTwice);
+    }
+
+    let source = read_json(&args.source)?;
+    let target = read_json(&args.target)?;
+
+    let mut builder = DiffBuilder::default();
+    for path in &args.ignore {
+        builder.ignore_path(path);
+    }
+    for path in &args.ignore_missing {
+        builder.ignore_path_with_missing(path, true);
+    }
+    for (path, keys) in &args.array_keys {
+        builder.array_keys(path, &keys.iter().map(String::as_str).collect::<Vec<_>>());
+    }
+    for path in &args.unordered {
+        builder.unordered_array(path);
+    }
+    for (path, tolerance) in &args.tolerances {
+        builder.number_tolerance_at(path, *tolerance);
+    }
+    if let Some(unit) = args.text_diff {
+        builder.text_diff(unit.into());
+    }
+    builder
+        .equate_empty_arrays(args.equate_empty_arrays)
+        .approx_float_eq_epsilon(args.float_epsilon)
+        .equate_integers_and_floats(!args.strict_numbers)
+        .approx_date_time_eq_duration(args.datetime_tolerance.unwrap_or_default())
+        .array_diff_mode(args.array_diff.into())
+        .compare_mode(args.mode.into())
+        .text_diff_min_length(args.text_diff_min_length);
+
+    let diff = builder.source(source).target(target).build()?;
+    let Some(difference) = diff.compare() else {
+        return Ok(true);
+    };
+
+    let source = diff.source();
+    let output = match args.output {
+        Output::Text => difference.render(args.color.into()),
+        Output::Unified => difference.unified_diff(source, args.context, args.color.into()),
+        Output::SideBySide => difference.side_by_side_diff(source, args.width, args.color.into()),
+        Output::Difference => serde_json::to_string_pretty(&difference)? + "\n",
+        Output::JsonPatch => serde_json::to_string_pretty(&difference.to_json_patch(source))? + "\n",
+        Output::MergePatch => serde_json::to_string_pretty(&difference.to_merge_patch(source)?)? + "\n",
+    };
+    // A closed stdout (e.g. `json-diff a b | head`) shouldn't turn a difference into an error.
+    let _ = io::stdout().lock().write_all(output.as_bytes());
+
+    Ok(false)
+}
+
+fn read_json(path: &str) -> Result<serde_json::Value, CliError> {
+    let content = if path == "-" {
+        let mut buf = String::new();
+        io::stdin().read_to_string(&mut buf).map(|_| buf)
+    } else {
+        fs::read_to_string(path)
+    }
+    .map_err(|source| CliError::Read { path: path.to_string(), source })?;
+
+    serde_json::from_str(&content).map_err(|source| CliError::Parse { path: path.to_string(), source })
+}
+
+fn parse_array_key(s: &str) -> Result<(String, Vec<String>), String> {
+    let (path, keys) = s.split_once('=').ok_or_else(|| format!("expected PATH=KEY, got `{s}`"))?;
+    let keys: Vec<_> = keys.split(',').map(str::to_string).collect();
+    if path.is_empty() || keys.iter().any(String::is_empty) {
+        return Err(format!("expected PATH=KEY, got `{s}`"));
+    }
+
+    Ok((path.to_string(), keys))
+}
+
+fn parse_tolerance(s: &str) -> Result<(String, Tolerance), String> {
+    let (path, tolerance) = s.rsplit_once('=').ok_or_else(|| format!("expected PATH=TOLERANCE, got `{s}`"))?;
+    let invalid = || format!("invalid tolerance `{tolerance}`");
+    let tolerance = if let Some(percent) = tolerance.strip_suffix('%') {
+        Tolerance::Relative(percent.parse::<f64>().map_err(|_| invalid())? / 100.0)
+    } else if let Some(ulps) = tolerance.strip_suffix("ulp") {
+        Tolerance::Ulps(ulps.parse().map_err(|_| invalid())?)
+    } else {
+        Tolerance::Absolute(tolerance.parse().map_err(|_| invalid())?)
+    };
+    if path.is_empty() {
+        return Err(format!("expected PATH=TOLERANCE, got `{s}`"));
+    }
+
+    Ok((path.to_string(), tolerance))
+}
+
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let split = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value: f64 = value.parse().map_err(|_| format!("invalid duration `{s}`"))?;
+    let secs = match unit {
+        "ms" => value / 1000.0,
+        "" | "s" => value,
+        "m" => value * 60.0,
+        "h" => value * 3600.0,
+        _ => return Err(format!("unknown duration unit `{unit}`, expected one of ms, s, m, h")),
+    };
+
+    Duration::try_from_secs_f64(secs).map_err(|err| format!("invalid duration `{s}`: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::Duration;
+    use clap::Parser;
+    use json_diff_rs::Tolerance;
+    use super::{parse_duration, parse_tolerance, run, Args};
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("1").unwrap(), Duration::from_secs(1));
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("2m").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_duration("1h").unwrap(), Duration::from_secs(3600));
+
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("1d").is_err());
+        assert!(parse_duration("-1s").is_err());
+    }
+
+    #[test]
+    fn tolerances() {
+        assert_eq!(parse_tolerance("prices.[_]=0.01").unwrap(), ("prices.[_]".to_string(), Tolerance::Absolute(0.01)));
+        assert_eq!(parse_tolerance("metrics.*=1%").unwrap(), ("metrics.*".to_string(), Tolerance::Relative(0.01)));
+        assert_eq!(parse_tolerance("$[?@.a=='b']=4ulp").unwrap(), ("$[?@.a=='b']".to_string(), Tolerance::Ulps(4)));
+
+        assert!(parse_tolerance("a").is_err());
+        assert!(parse_tolerance("=1").is_err());
+        assert!(parse_tolerance("a=1x").is_err());
+    }
+
+    #[test]
+    fn strict_numbers() {
+        let path = |name: &str| std::env::temp_dir().join(format!("json-diff-{}-{name}.json", std::process::id()));
+        let (source, target) = (path("source"), path("target"));
+        fs::write(&source, r#"{"a": 1}"#).unwrap();
+        fs::write(&target, r#"{"a": 1.0}"#).unwrap();
+        let args = |flags: &[&str]| {
+            let paths = [source.to_str().unwrap(), target.to_str().unwrap()];
+            Args::try_parse_from(["json-diff"].iter().chain(&paths).chain(flags)).unwrap()
+        };
+
+        assert!(run(args(&[])).unwrap());
+        assert!(!run(args(&["--strict-numbers", "--output", "text"])).unwrap());
+
+        fs::remove_file(source).unwrap();
+        fs::remove_file(target).unwrap();
+    }
+}
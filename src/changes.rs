@@ -0,0 +1,189 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::visitor::{walk, DifferenceVisitor};
+use crate::{ArrayIndex, Difference, Path, PathElement, ScalarDifference, Type};
+
+/// A single change found by a comparison, without the structure of the documents around it.
+#[derive(Debug, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum Change<'a> {
+    /// A value that `target` has, and `source` doesn't
+    Added { value: Cow<'a, Value> },
+    /// A value that `source` has, and `target` doesn't
+    Removed { value: Cow<'a, Value> },
+    /// A scalar whose value changed
+    Scalar(Cow<'a, ScalarDifference<'a>>),
+    /// A value whose type changed
+    Type {
+        source_type: Type,
+        source_value: Cow<'a, Value>,
+        target_type: Type,
+        target_value: Cow<'a, Value>,
+    },
+    /// Values that a [`Comparator`](crate::Comparator) found different as a whole
+    Custom {
+        source_value: Cow<'a, Value>,
+        target_value: Cow<'a, Value>,
+        /// The path the comparator was registered for, if any
+        comparator: Option<Cow<'a, str>>,
+    },
+    /// An element that `source` has past the end of the array in `target`, whose value isn't recorded
+    ExtraElement,
+}
+
+impl Change<'_> {
+    /// Converts this change into one that doesn't borrow from the difference it was found in.
+    pub fn into_owned(self) -> Change<'static> {
+        let owned = |value: Cow<Value>| Cow::Owned(value.into_owned());
+        match self {
+            Change::Added { value } => Change::Added { value: owned(value) },
+            Change::Removed { value } => Change::Removed { value: owned(value) },
+            Change::Scalar(diff) => Change::Scalar(Cow::Owned(diff.into_owned().into_owned())),
+            Change::Type { source_type, source_value, target_type, target_value } => Change::Type {
+                source_type,
+                source_value: owned(source_value),
+                target_type,
+                target_value: owned(target_value),
+            },
+            Change::Custom { source_value, target_value, comparator } => Change::Custom {
+                source_value: owned(source_value),
+                target_value: owned(target_value),
+                comparator: comparator.map(|comparator| Cow::Owned(comparator.into_owned())),
+            },
+            Change::ExtraElement => Change::ExtraElement,
+        }
+    }
+}
+
+impl Difference<'_> {
+    /// Flattens the difference into its leaf changes, each with the absolute path of the changed
+    /// value, in the same order and at the same paths as [`walk`] reports them. The changes borrow
+    /// their values from the difference.
+    pub fn changes(&self) -> Vec<(Path, Change<'_>)> {
+        let mut collector = Collector(Vec::new());
+        walk(self, &mut collector);
+        collector.0
+    }
+}
+
+/// Collects the changes reported by [`walk`].
+struct Collector<'d>(Vec<(Path, Change<'d>)>);
+
+impl<'d> Collector<'d> {
+    fn change(&mut self, path: &Path, change: Change<'d>) {
+        self.0.push((path.clone(), change));
+    }
+}
+
+impl<'d> DifferenceVisitor<'d> for Collector<'d> {
+    fn added(&mut self, path: &Path, value: &'d Value) {
+        self.change(path, Change::Added { value: Cow::Borrowed(value) });
+    }
+
+    fn removed(&mut self, path: &Path, value: &'d Value) {
+        self.change(path, Change::Removed { value: Cow::Borrowed(value) });
+    }
+
+    fn scalar_changed(&mut self, path: &Path, diff: &'d ScalarDifference<'_>) {
+        self.change(path, Change::Scalar(Cow::Borrowed(diff)));
+    }
+
+    fn type_changed(&mut self, path: &Path, source: &'d Value, target: &'d Value) {
+        self.change(path, Change::Type {
+            source_type: Type::from(source),
+            source_value: Cow::Borrowed(source),
+            target_type: Type::from(target),
+            target_value: Cow::Borrowed(target),
+        });
+    }
+
+    fn custom_changed(&mut self, path: &Path, source: &'d Value, target: &'d Value, comparator: Option<&'d str>) {
+        self.change(path, Change::Custom {
+            source_value: Cow::Borrowed(source),
+            target_value: Cow::Borrowed(target),
+            comparator: comparator.map(Cow::Borrowed),
+        });
+    }
+
+    fn extra_elements(&mut self, path: &Path, indices: Range<usize>) {
+        for index in indices {
+            let mut path = path.clone();
+            path.push(PathElement::ArrayIndex(ArrayIndex::Index(index)));
+            self.0.push((path, Change::ExtraElement));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{ArrayDiffMode, DiffBuilder};
+    use super::*;
+
+    #[test]
+    fn test_changes() {
+        let source = json!({
+            "user": {"name": "John", "age": 30},
+            "removed": 1,
+            "tags": ["a", "b"],
+            "list": [1, 2, 3],
+            "short": [1],
+        });
+        let target = json!({
+            "user": {"name": "Joe", "age": "30"},
+            "added": true,
+            "tags": ["x", "a"],
+            "list": [1, 5],
+            "short": [1, 2],
+        });
+
+        let diff = DiffBuilder::default()
+            .array_diff_mode_at("tags", ArrayDiffMode::Lcs)
+            .source(source)
+            .target(target)
+            .build()
+            .unwrap();
+
+        let changes: Vec<_> = diff
+            .changes()
+            .into_iter()
+            .map(|(path, change)| (path.to_json_pointer().unwrap(), serde_json::to_value(change).unwrap()))
+            .collect();
+
+        assert_eq!(changes, [
+            ("/list/1".to_string(), json!({"change": "scalar", "source": 2, "target": 5})),
+            ("/list/2".to_string(), json!({"change": "extra_element"})),
+            ("/removed".to_string(), json!({"change": "removed", "value": 1})),
+            ("/short/1".to_string(), json!({"change": "added", "value": 2})),
+            ("/tags/0".to_string(), json!({"change": "added", "value": "x"})),
+            ("/tags/1".to_string(), json!({"change": "removed", "value": "b"})),
+            ("/user/age".to_string(), json!({
+                "change": "type",
+                "source_type": "number",
+                "source_value": 30,
+                "target_type": "string",
+                "target_value": "30",
+            })),
+            ("/user/name".to_string(), json!({"change": "scalar", "source": "John", "target": "Joe"})),
+            ("/added".to_string(), json!({"change": "added", "value": true})),
+        ]);
+
+        let difference = diff.compare().unwrap();
+        assert!(difference.changes().iter().all(|(_, change)| match change {
+            Change::Added { value } | Change::Removed { value } => matches!(value, Cow::Borrowed(_)),
+            Change::Scalar(diff) => matches!(diff, Cow::Borrowed(_)),
+            Change::Type { source_value, target_value, .. } => {
+                matches!((source_value, target_value), (Cow::Borrowed(_), Cow::Borrowed(_)))
+            }
+            Change::Custom { .. } | Change::ExtraElement => true,
+        }));
+
+        let diff = DiffBuilder::default().source(json!([1])).target(json!([1])).build().unwrap();
+
+        assert!(diff.changes().is_empty());
+    }
+}
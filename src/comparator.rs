@@ -0,0 +1,142 @@
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::json_path::JsonPath;
+use crate::Path;
+
+/// What a [`Comparator`] decided about a pair of values.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    /// The values are equal.
+    Equal,
+    /// The values are different as a whole, reported as a
+    /// [`Difference::Custom`](crate::Difference::Custom).
+    Different,
+    /// The comparator has no opinion: the next matching comparator is consulted, and then the
+    /// built-in comparison.
+    Recurse,
+}
+
+/// Domain-specific equality for some values, e.g. case-insensitive UUIDs.
+///
+/// Comparators are registered with [`DiffBuilder::comparator`](crate::DiffBuilder::comparator)
+/// and are consulted in the order they were registered, before the built-in comparison of the
+/// values at matching paths. Closures taking the same arguments are comparators too.
+pub trait Comparator: Send + Sync {
+    /// Compares `source` and `target`, the values at `path`.
+    fn compare(&self, path: &Path, source: &Value, target: &Value) -> Outcome;
+}
+
+impl<F> Comparator for F
+where
+    F: Fn(&Path, &Value, &Value) -> Outcome + Send + Sync,
+{
+    fn compare(&self, path: &Path, source: &Value, target: &Value) -> Outcome {
+        self(path, source, target)
+    }
+}
+
+/// A comparator and the values it applies to.
+#[derive(Clone)]
+pub(crate) struct ComparatorRule {
+    /// The path the comparator was registered for, if any
+    pub(crate) path: Option<String>,
+    pub(crate) when: When,
+    pub(crate) comparator: Arc<dyn Comparator>,
+}
+
+#[derive(Clone)]
+pub(crate) enum When {
+    Path(Path),
+    JsonPath(JsonPath),
+    Predicate(Arc<dyn Fn(&Path) -> bool + Send + Sync>),
+}
+
+impl Debug for ComparatorRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComparatorRule").field("path", &self.path).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{DiffBuilder, PathElement};
+    use super::*;
+
+    fn case_insensitive(_: &Path, source: &Value, target: &Value) -> Outcome {
+        match (source.as_str(), target.as_str()) {
+            (Some(source), Some(target)) if source.eq_ignore_ascii_case(target) => Outcome::Equal,
+            (Some(_), Some(_)) => Outcome::Different,
+            _ => Outcome::Recurse,
+        }
+    }
+
+    #[test]
+    fn test_comparators() {
+        let source = json!({
+            "id": "ABC",
+            "owner_id": "DEF",
+            "name": "John",
+            "price": {"amount": 1.004, "currency": "EUR"},
+            "tags": ["a", "b"],
+        });
+        let target = json!({
+            "id": "abc",
+            "owner_id": "deg",
+            "name": "john",
+            "price": {"amount": 1.0, "currency": "EUR"},
+            "tags": ["b", "a"],
+        });
+
+        let diff = DiffBuilder::default()
+            .comparator("id", case_insensitive)
+            .comparator_when(|path| matches!(path.last(), Some(PathElement::Key(key)) if key.ends_with("_id")), case_insensitive)
+            .comparator("/price/amount", |_: &Path, source: &Value, target: &Value| {
+                match (source.as_f64().unwrap() * 100.0).round() == (target.as_f64().unwrap() * 100.0).round() {
+                    true => Outcome::Equal,
+                    false => Outcome::Different,
+                }
+            })
+            .comparator("tags", |_: &Path, _: &Value, _: &Value| Outcome::Recurse)
+            .comparator("tags", |_: &Path, source: &Value, target: &Value| {
+                let mut source = source.as_array().unwrap().clone();
+                let mut target = target.as_array().unwrap().clone();
+                source.sort_by_key(Value::to_string);
+                target.sort_by_key(Value::to_string);
+                match source == target {
+                    true => Outcome::Equal,
+                    false => Outcome::Different,
+                }
+            })
+            .source(source)
+            .target(target)
+            .build()
+            .unwrap();
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), [
+            "name: - \"John\" / + \"john\"",
+            "owner_id: - \"DEF\" / + \"deg\" (different by a comparator)",
+            "",
+        ].join("\n"));
+
+        let diff = DiffBuilder::default()
+            .comparator("a", |_: &Path, _: &Value, _: &Value| Outcome::Different)
+            .source(json!({"a": [1]}))
+            .target(json!({"a": [1]}))
+            .build()
+            .unwrap();
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), "a: - [1] / + [1] (different by the comparator for `a`)\n");
+        assert_eq!(serde_json::to_value(&diff).unwrap()["different_entries"]["a"]["value_diff"], json!({
+            "difference_of": "custom",
+            "source_value": [1],
+            "target_value": [1],
+            "comparator": "a",
+        }));
+    }
+}
@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use serde::Serializer;
+use serde_json::Value;
+
+/// A way of writing datetimes in JSON.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DateTimeFormat {
+    /// Strings like `2023-07-25T15:30:00+02:00`.
+    Rfc3339,
+    /// Strings like `Tue, 25 Jul 2023 15:30:00 +0200`.
+    Rfc2822,
+    /// Numbers of seconds since the Unix epoch, e.g. `1690291800` or `1690291800.5`.
+    EpochSeconds,
+    /// Numbers of milliseconds since the Unix epoch, e.g. `1690291800500`.
+    EpochMillis,
+    /// Strings like `2023-07-25 15:30:00`, with optional fractional seconds, in UTC.
+    Naive,
+}
+
+/// How the datetimes at a path are compared, see [`DiffBuilder::date_time_at`](crate::DiffBuilder::date_time_at).
+///
+/// Datetimes are compared as instants, regardless of the timezone they are written in, and
+/// values that don't parse as any of the `formats` are compared as usual.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DateTimeOptions {
+    /// The formats to try, in order. Both values may be written in different formats.
+    pub formats: Vec<DateTimeFormat>,
+    /// Datetimes that are at most this far apart are equal.
+    pub tolerance: Duration,
+    /// Only compare the dates, in UTC, so the tolerance must be a whole number of days.
+    pub date_only: bool,
+}
+
+impl Default for DateTimeOptions {
+    fn default() -> Self {
+        DateTimeOptions {
+            formats: vec![DateTimeFormat::Rfc3339],
+            tolerance: Duration::ZERO,
+            date_only: false,
+        }
+    }
+}
+
+impl DateTimeOptions {
+    /// How much later `target` is than `source`, or `None` if either isn't a datetime.
+    pub(crate) fn delta(&self, source: &Value, target: &Value) -> Option<TimeDelta> {
+        let source = self.parse(source)?;
+        let target = self.parse(target)?;
+        Some(match self.date_only {
+            true => target.date_naive() - source.date_naive(),
+            false => target - source,
+        })
+    }
+
+    /// Whether `delta` is within the tolerance.
+    pub(crate) fn is_within(&self, delta: TimeDelta) -> bool {
+        delta.abs().to_std().is_ok_and(|delta| delta <= self.tolerance)
+    }
+
+    fn parse(&self, value: &Value) -> Option<DateTime<Utc>> {
+        self.formats.iter().find_map(|format| match (format, value) {
+            (DateTimeFormat::Rfc3339, Value::String(s)) => DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.to_utc()),
+            (DateTimeFormat::Rfc2822, Value::String(s)) => DateTime::parse_from_rfc2822(s).ok().map(|dt| dt.to_utc()),
+            (DateTimeFormat::Naive, Value::String(s)) => {
+                NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f").ok().map(|dt| dt.and_utc())
+            }
+            (DateTimeFormat::EpochSeconds, Value::Number(n)) => match n.as_i64() {
+                Some(secs) => DateTime::from_timestamp(secs, 0),
+                None => n.as_f64().and_then(|secs| {
+                    DateTime::from_timestamp(secs.floor() as i64, ((secs - secs.floor()) * 1e9) as u32)
+                }),
+            },
+            (DateTimeFormat::EpochMillis, Value::Number(n)) => match n.as_i64() {
+                Some(millis) => DateTime::from_timestamp_millis(millis),
+                None => n.as_f64().and_then(|millis| DateTime::from_timestamp_micros((millis * 1e3) as i64)),
+            },
+            _ => None,
+        })
+    }
+}
+
+/// The number of seconds in `delta`.
+pub(crate) fn seconds(delta: TimeDelta) -> f64 {
+    delta.num_seconds() as f64 + f64::from(delta.subsec_nanos()) / 1e9
+}
+
+/// Serializes a delta as a number of seconds.
+pub(crate) fn serialize_delta<S: Serializer>(delta: &TimeDelta, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(seconds(*delta))
+}
+
+/// Serializes a duration as a number of seconds.
+pub(crate) fn serialize_duration<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    #[test]
+    fn test_date_time_delta() {
+        let options = DateTimeOptions {
+            formats: vec![
+                DateTimeFormat::Rfc3339,
+                DateTimeFormat::Rfc2822,
+                DateTimeFormat::Naive,
+                DateTimeFormat::EpochSeconds,
+            ],
+            ..Default::default()
+        };
+        let delta = |source: Value, target: Value| options.delta(&source, &target);
+
+        assert_eq!(delta(json!("2023-07-25T15:30:00+02:00"), json!("2023-07-25T13:30:01Z")), Some(TimeDelta::seconds(1)));
+        assert_eq!(delta(json!("Tue, 25 Jul 2023 15:30:00 +0200"), json!("2023-07-25 13:30:00.5")), Some(TimeDelta::milliseconds(500)));
+        assert_eq!(delta(json!(1690291800), json!("2023-07-25T13:30:00Z")), Some(TimeDelta::zero()));
+        assert_eq!(delta(json!(1690291800.25), json!(1690291800)), Some(TimeDelta::milliseconds(-250)));
+        assert_eq!(delta(json!("2023-07-25"), json!("2023-07-25T13:30:00Z")), None);
+
+        let options = DateTimeOptions {
+            formats: vec![DateTimeFormat::EpochMillis, DateTimeFormat::Rfc3339],
+            tolerance: Duration::from_secs(86400),
+            date_only: true,
+        };
+
+        let delta = options.delta(&json!(1690329599999_u64), &json!("2023-07-27T00:00:00Z")).unwrap();
+        assert_eq!(delta, TimeDelta::days(2));
+        assert!(!options.is_within(delta));
+        assert!(options.is_within(-TimeDelta::days(1)));
+    }
+}
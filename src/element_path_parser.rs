@@ -1,31 +1,36 @@
-use crate::{ArrayIndex, PathElement};
+use crate::{ArrayIndex, PathElement, PathError};
 
-pub fn parse_element_path(s: &str) -> Result<Vec<PathElement>, String> {
+pub fn parse_element_path(s: &str) -> Result<Vec<PathElement>, PathError> {
     if s.is_empty() {
-        return Err("Empty path is not allowed".to_string());
+        return Err(PathError::new(0, "Empty path is not allowed"));
     }
 
     let mut result = Vec::new();
-    let mut chars = s.chars().peekable();
+    let mut chars = s.char_indices().peekable();
     let mut current = String::new();
     let mut in_quotes = false;
     let mut in_brackets = false;
+    // Where the current quote or bracket was opened
+    let mut opened_at = 0;
 
-    while let Some(c) = chars.next() {
+    while let Some((i, c)) = chars.next() {
         match c {
             '\'' => {
                 if in_quotes {
-                    if current.is_empty() {
-                        return Err("Empty quoted string is not allowed".to_string());
+                    // A doubled quote inside quotes is a literal quote.
+                    if chars.next_if(|&(_, c)| c == '\'').is_some() {
+                        current.push(c);
+                        continue;
                     }
                     result.push(PathElement::Key(current.clone()));
                     current.clear();
                     in_quotes = false;
                 } else {
                     if !current.is_empty() {
-                        return Err("Unexpected quote".to_string());
+                        return Err(PathError::new(i, "Unexpected quote"));
                     }
                     in_quotes = true;
+                    opened_at = i;
                 }
             }
             '.' => {
@@ -33,10 +38,10 @@ pub fn parse_element_path(s: &str) -> Result<Vec<PathElement>, String> {
                     current.push(c);
                 } else {
                     if !current.is_empty() {
-                        result.push(PathElement::Key(current.clone()));
+                        result.push(unquoted_key(&current));
                         current.clear();
                     } else if result.is_empty() {
-                        return Err("Path cannot start with a dot".to_string());
+                        return Err(PathError::new(i, "Path cannot start with a dot"));
                     }
                 }
             }
@@ -45,28 +50,29 @@ pub fn parse_element_path(s: &str) -> Result<Vec<PathElement>, String> {
                     current.push(c);
                 } else {
                     if !current.is_empty() {
-                        result.push(PathElement::Key(current.clone()));
+                        result.push(unquoted_key(&current));
                         current.clear();
                     }
                     in_brackets = true;
+                    opened_at = i;
                 }
             }
             ']' => {
                 if in_quotes {
                     current.push(c);
                 } else if in_brackets {
-                    if current == "_" {
+                    if current == "_" || current == "*" {
                         result.push(PathElement::ArrayIndex(ArrayIndex::All));
                     } else {
                         match current.parse::<usize>() {
                             Ok(index) => result.push(PathElement::ArrayIndex(ArrayIndex::Index(index))),
-                            Err(_) => return Err(format!("Invalid array index: {}", current)),
+                            Err(_) => return Err(PathError::new(opened_at + 1, format!("Invalid array index: {}", current))),
                         }
                     }
                     current.clear();
                     in_brackets = false;
                 } else {
-                    return Err("Unexpected closing bracket".to_string());
+                    return Err(PathError::new(i, "Unexpected closing bracket"));
                 }
             }
             _ => {
@@ -76,24 +82,33 @@ pub fn parse_element_path(s: &str) -> Result<Vec<PathElement>, String> {
     }
 
     if in_quotes {
-        return Err("Unclosed quote".to_string());
+        return Err(PathError::new(opened_at, "Unclosed quote"));
     }
 
     if in_brackets {
-        return Err("Unclosed bracket".to_string());
+        return Err(PathError::new(opened_at, "Unclosed bracket"));
     }
 
     if !current.is_empty() {
-        result.push(PathElement::Key(current));
+        result.push(unquoted_key(&current));
     }
 
     if result.is_empty() {
-        return Err("Empty path is not allowed".to_string());
+        return Err(PathError::new(0, "Empty path is not allowed"));
     }
 
     Ok(result)
 }
 
+/// `*` and `**` are wildcards unless they are quoted.
+fn unquoted_key(key: &str) -> PathElement {
+    match key {
+        "*" => PathElement::AnyKey,
+        "**" => PathElement::AnyDepth,
+        _ => PathElement::Key(key.to_string()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{ArrayIndex, PathElement};
@@ -144,10 +159,34 @@ mod tests {
             ]
         );
 
+        assert_eq!(
+            parse_element_path("**.users.*.[*].'*'").unwrap(),
+            vec![
+                PathElement::AnyDepth,
+                PathElement::Key("users".to_string()),
+                PathElement::AnyKey,
+                PathElement::ArrayIndex(ArrayIndex::All),
+                PathElement::Key("*".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            parse_element_path("'it''s'.''.''''").unwrap(),
+            vec![
+                PathElement::Key("it's".to_string()),
+                PathElement::Key(String::new()),
+                PathElement::Key("'".to_string()),
+            ]
+        );
+
         assert!(parse_element_path("").is_err());
-        assert!(parse_element_path("''").is_err());
+        assert!(parse_element_path("'''").is_err());
         assert!(parse_element_path("a.'").is_err());
         assert!(parse_element_path("a.[").is_err());
         assert!(parse_element_path("a.[x]").is_err());
+
+        assert_eq!(parse_element_path("a.[x]").unwrap_err(), PathError::new(3, "Invalid array index: x"));
+        assert_eq!(parse_element_path("a.b]").unwrap_err(), PathError::new(3, "Unexpected closing bracket"));
+        assert_eq!(parse_element_path("a.'b").unwrap_err(), PathError::new(2, "Unclosed quote"));
     }
 }
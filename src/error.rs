@@ -0,0 +1,38 @@
+use derive_builder::UninitializedFieldError;
+use thiserror::Error;
+
+/// An error in the configuration of a [`Diff`](crate::Diff), returned by [`DiffBuilder::build`](crate::DiffBuilder::build).
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum Error {
+    #[error("invalid path `{path}`: {source}")]
+    InvalidPath { path: String, source: PathError },
+    #[error("invalid `{option}`: {reason}")]
+    InvalidOption { option: &'static str, reason: String },
+    #[error("conflicting rules for `{path}`: {reason}")]
+    ConflictingRules { path: String, reason: String },
+    #[error("failed to serialize the {document}: {reason}")]
+    Serialize { document: &'static str, reason: String },
+    #[error("`{0}` must be set")]
+    UninitializedField(&'static str),
+}
+
+impl From<UninitializedFieldError> for Error {
+    fn from(err: UninitializedFieldError) -> Self {
+        Error::UninitializedField(err.field_name())
+    }
+}
+
+/// An error in the syntax of a [`Path`](crate::Path).
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{message} at position {position}")]
+pub struct PathError {
+    /// Byte offset in the path where the error was found
+    pub position: usize,
+    pub message: String,
+}
+
+impl PathError {
+    pub(crate) fn new(position: usize, message: impl Into<String>) -> Self {
+        Self { position, message: message.into() }
+    }
+}
@@ -0,0 +1,531 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{ArrayDifference, ArrayEdit, Difference, DumbMap, EntryDifference, ScalarDifference};
+
+/// A single [RFC 6902](https://www.rfc-editor.org/rfc/rfc6902) JSON Patch operation.
+///
+/// Paths are [RFC 6901](https://www.rfc-editor.org/rfc/rfc6901) JSON Pointers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+/// Whether `test` operations are checked when applying a patch.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ApplyMode {
+    /// A failing `test` operation aborts the patch.
+    #[default]
+    Strict,
+    /// `test` operations are skipped, even if their path doesn't exist.
+    Lenient,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum PatchError {
+    #[error("invalid JSON Pointer `{0}`")]
+    InvalidPointer(String),
+    #[error("nothing to operate on at `{0}`")]
+    PathNotFound(String),
+    #[error("invalid array index at `{0}`")]
+    InvalidIndex(String),
+    #[error("cannot move `{from}` into its own child `{path}`")]
+    MoveIntoChild { from: String, path: String },
+    #[error("test failed at `{path}`: expected {expected}, found {actual}")]
+    TestFailed { path: String, expected: Value, actual: Value },
+}
+
+/// Applies JSON Patch `operations` to `source` and returns the patched document.
+///
+/// Operations are applied in order, and `source` is left untouched if one of them fails.
+pub fn apply_patch(source: &Value, operations: &[PatchOperation], mode: ApplyMode) -> Result<Value, PatchError> {
+    let mut value = source.clone();
+    for operation in operations {
+        match operation {
+            PatchOperation::Add { path, value: new } => add(&mut value, path, new.clone())?,
+            PatchOperation::Remove { path } => {
+                remove(&mut value, path)?;
+            }
+            PatchOperation::Replace { path, value: new } => *pointer_mut(&mut value, path)? = new.clone(),
+            PatchOperation::Move { from, path } => {
+                if from == path {
+                    continue;
+                }
+                if path.starts_with(from.as_str()) && path[from.len()..].starts_with('/') {
+                    return Err(PatchError::MoveIntoChild { from: from.clone(), path: path.clone() });
+                }
+                let moved = remove(&mut value, from)?;
+                add(&mut value, path, moved)?;
+            }
+            PatchOperation::Copy { from, path } => {
+                let copied = pointer_mut(&mut value, from)?.clone();
+                add(&mut value, path, copied)?;
+            }
+            PatchOperation::Test { .. } if mode == ApplyMode::Lenient => {}
+            PatchOperation::Test { path, value: expected } => {
+                let actual = pointer_mut(&mut value, path)?;
+                if actual != expected {
+                    return Err(PatchError::TestFailed {
+                        path: path.clone(),
+                        expected: expected.clone(),
+                        actual: actual.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(value)
+}
+
+fn add(value: &mut Value, path: &str, new: Value) -> Result<(), PatchError> {
+    let Some((parent, last)) = split_pointer(path)? else {
+        *value = new;
+        return Ok(());
+    };
+
+    match pointer_mut(value, parent)? {
+        Value::Object(map) => {
+            map.insert(last, new);
+        }
+        Value::Array(values) if last == "-" => values.push(new),
+        Value::Array(values) => {
+            let index = array_index(&last, path)?;
+            if index > values.len() {
+                return Err(PatchError::InvalidIndex(path.to_string()));
+            }
+            values.insert(index, new);
+        }
+        _ => return Err(PatchError::PathNotFound(path.to_string())),
+    }
+
+    Ok(())
+}
+
+fn remove(value: &mut Value, path: &str) -> Result<Value, PatchError> {
+    let Some((parent, last)) = split_pointer(path)? else {
+        return Ok(std::mem::take(value));
+    };
+
+    match pointer_mut(value, parent)? {
+        Value::Object(map) => map.remove(&last).ok_or_else(|| PatchError::PathNotFound(path.to_string())),
+        Value::Array(values) => {
+            let index = array_index(&last, path)?;
+            if index >= values.len() {
+                return Err(PatchError::InvalidIndex(path.to_string()));
+            }
+            Ok(values.remove(index))
+        }
+        _ => Err(PatchError::PathNotFound(path.to_string())),
+    }
+}
+
+fn pointer_mut<'a>(value: &'a mut Value, path: &str) -> Result<&'a mut Value, PatchError> {
+    if !path.is_empty() && !path.starts_with('/') {
+        return Err(PatchError::InvalidPointer(path.to_string()));
+    }
+
+    path.split('/').skip(1).try_fold(value, |value, token| {
+        let token = unescape_pointer_token(token).ok_or_else(|| PatchError::InvalidPointer(path.to_string()))?;
+        match value {
+            Value::Object(map) => map.get_mut(&token),
+            Value::Array(values) => values.get_mut(array_index(&token, path)?),
+            _ => None,
+        }
+        .ok_or_else(|| PatchError::PathNotFound(path.to_string()))
+    })
+}
+
+/// Splits a pointer into its parent pointer and unescaped last token, or `None` for the root.
+fn split_pointer(path: &str) -> Result<Option<(&str, String)>, PatchError> {
+    if path.is_empty() {
+        return Ok(None);
+    }
+    let (parent, last) = path.rsplit_once('/').ok_or_else(|| PatchError::InvalidPointer(path.to_string()))?;
+    let last = unescape_pointer_token(last).ok_or_else(|| PatchError::InvalidPointer(path.to_string()))?;
+
+    Ok(Some((parent, last)))
+}
+
+fn array_index(token: &str, path: &str) -> Result<usize, PatchError> {
+    let canonical = token == "0" || !token.starts_with('0') && token.bytes().all(|b| b.is_ascii_digit());
+    match canonical {
+        true => token.parse().map_err(|_| PatchError::InvalidIndex(path.to_string())),
+        false => Err(PatchError::InvalidIndex(path.to_string())),
+    }
+}
+
+impl Difference<'_> {
+    /// Applies the difference to `source`, which must be the document it was computed from.
+    pub fn apply(&self, source: &Value) -> Result<Value, PatchError> {
+        apply_patch(source, &self.to_json_patch(source), ApplyMode::Strict)
+    }
+
+    /// Converts the difference into JSON Patch operations that turn `source` into the compared target.
+    ///
+    /// `source` must be the document the difference was computed from. Only `add`, `remove`,
+    /// `replace` and, for arrays compared regardless of order, `move` operations are generated.
+    /// Arrays that are equal regardless of order aren't part of the difference, so they keep the
+    /// order of `source`.
+    pub fn to_json_patch(&self, source: &Value) -> Vec<PatchOperation> {
+        let mut operations = Vec::new();
+        self.patch_operations(&mut String::new(), source, &mut operations);
+        operations
+    }
+
+    fn patch_operations(&self, pointer: &mut String, source: &Value, operations: &mut Vec<PatchOperation>) {
+        match self {
+            Difference::Scalar(diff) => operations.push(PatchOperation::Replace {
+                path: pointer.clone(),
+                value: diff.target_value(),
+            }),
+            Difference::Type { target_value, .. } | Difference::Custom { target_value, .. } => {
+                operations.push(PatchOperation::Replace { path: pointer.clone(), value: target_value.clone().into_owned() })
+            }
+            Difference::Object { different_entries } => {
+                for (key, entry) in &different_entries.0 {
+                    with_token(pointer, &escape_pointer_token(key), |pointer| match entry {
+                        EntryDifference::Missing { value } => operations.push(PatchOperation::Add {
+                            path: pointer.clone(),
+                            value: value.clone().into_owned(),
+                        }),
+                        EntryDifference::Extra { .. } => operations.push(PatchOperation::Remove {
+                            path: pointer.clone(),
+                        }),
+                        EntryDifference::Value { value_diff } => {
+                            value_diff.patch_operations(pointer, &source[key.as_ref()], operations)
+                        }
+                    });
+                }
+            }
+            Difference::Array(diff) => diff.patch_operations(pointer, source, operations),
+        }
+    }
+}
+
+impl ArrayDifference<'_> {
+    fn patch_operations(&self, pointer: &mut String, source: &Value, operations: &mut Vec<PatchOperation>) {
+        let source_len = source.as_array().map_or(0, Vec::len);
+        let mut pairs = |different_pairs: &DumbMap<usize, Difference>, operations: &mut Vec<PatchOperation>| {
+            for (index, diff) in &different_pairs.0 {
+                with_token(pointer, &index.to_string(), |pointer| {
+                    diff.patch_operations(pointer, &source[index], operations)
+                });
+            }
+        };
+
+        match self {
+            ArrayDifference::PairsOnly { different_pairs } => pairs(different_pairs, operations),
+            ArrayDifference::Shorter { different_pairs, missing_elements, source_length } => {
+                if let Some(different_pairs) = different_pairs {
+                    pairs(different_pairs, operations);
+                }
+                for (index, value) in (*source_length..).zip(missing_elements) {
+                    operations.push(PatchOperation::Add {
+                        path: format!("{pointer}/{index}"),
+                        value: value.clone().into_owned(),
+                    });
+                }
+            }
+            ArrayDifference::Longer { different_pairs, extra_length, target_length } => {
+                if let Some(different_pairs) = different_pairs {
+                    pairs(different_pairs, operations);
+                }
+                for index in (*target_length..target_length + extra_length).rev() {
+                    operations.push(PatchOperation::Remove { path: format!("{pointer}/{index}") });
+                }
+            }
+            ArrayDifference::EditScript { edits } => {
+                // Edits are ordered, so everything before the current edit already matches the target.
+                let mut shift = 0isize;
+                for edit in edits {
+                    match edit {
+                        ArrayEdit::Insert { index, value } => {
+                            operations.push(PatchOperation::Add {
+                                path: format!("{pointer}/{index}"),
+                                value: value.clone().into_owned(),
+                            });
+                            shift += 1;
+                        }
+                        ArrayEdit::Delete { index, .. } => {
+                            let index = index.saturating_add_signed(shift);
+                            operations.push(PatchOperation::Remove { path: format!("{pointer}/{index}") });
+                            shift -= 1;
+                        }
+                        ArrayEdit::Modify { source_index, target_index, value_diff } => {
+                            with_token(pointer, &target_index.to_string(), |pointer| {
+                                value_diff.patch_operations(pointer, &source[source_index], operations)
+                            });
+                        }
+                    }
+                }
+            }
+            ArrayDifference::Matched { edits, moved } => {
+                let mut target_indices: HashMap<usize, usize> = moved.iter().copied().collect();
+                let mut deleted = Vec::new();
+                let mut inserted = Vec::new();
+                for edit in edits {
+                    match edit {
+                        ArrayEdit::Modify { source_index, target_index, value_diff } => {
+                            target_indices.insert(*source_index, *target_index);
+                            with_token(pointer, &source_index.to_string(), |pointer| {
+                                value_diff.patch_operations(pointer, &source[source_index], operations)
+                            });
+                        }
+                        ArrayEdit::Delete { index, .. } => deleted.push(*index),
+                        ArrayEdit::Insert { index, value } => inserted.push((*index, value)),
+                    }
+                }
+                deleted.sort_unstable();
+                for index in deleted.iter().rev() {
+                    operations.push(PatchOperation::Remove { path: format!("{pointer}/{index}") });
+                }
+
+                // The remaining elements by their source index, and where they and the inserted
+                // elements end up in `target`. Elements that aren't mentioned kept their index.
+                let mut current: Vec<Option<usize>> =
+                    (0..source_len).filter(|i| deleted.binary_search(i).is_err()).map(Some).collect();
+                let mut slots: Vec<_> = current
+                    .iter()
+                    .flatten()
+                    .map(|&i| (target_indices.get(&i).copied().unwrap_or(i), Slot::Kept(i)))
+                    .chain(inserted.into_iter().map(|(index, value)| (index, Slot::Inserted(value))))
+                    .collect();
+                slots.sort_by_key(|&(index, _)| index);
+
+                // Everything before `index` is already in place.
+                for (index, (_, slot)) in slots.into_iter().enumerate() {
+                    match slot {
+                        Slot::Inserted(value) => {
+                            operations.push(PatchOperation::Add {
+                                path: format!("{pointer}/{index}"),
+                                value: value.clone().into_owned(),
+                            });
+                            current.insert(index, None);
+                        }
+                        Slot::Kept(i) => {
+                            let from = current.iter().position(|&element| element == Some(i)).unwrap_or(index);
+                            if from != index {
+                                operations.push(PatchOperation::Move {
+                                    from: format!("{pointer}/{from}"),
+                                    path: format!("{pointer}/{index}"),
+                                });
+                                let element = current.remove(from);
+                                current.insert(index, element);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An element of a patched array, see [`ArrayDifference::Matched`].
+enum Slot<'v> {
+    /// The element of `source` at this index
+    Kept(usize),
+    Inserted(&'v Cow<'v, Value>),
+}
+
+impl ScalarDifference<'_> {
+    pub(crate) fn source_value(&self) -> Value {
+        match self {
+            ScalarDifference::Bool { source, .. } => Value::Bool(*source),
+            ScalarDifference::String { source, .. } => Value::String(source.to_string()),
+            ScalarDifference::Number { source, .. } | ScalarDifference::ApproxNumber { source, .. } => {
+                Value::Number(source.clone())
+            }
+            ScalarDifference::DateTime { source, .. } => source.clone().into_owned(),
+        }
+    }
+
+    pub(crate) fn target_value(&self) -> Value {
+        match self {
+            ScalarDifference::Bool { target, .. } => Value::Bool(*target),
+            ScalarDifference::String { target, .. } => Value::String(target.to_string()),
+            ScalarDifference::Number { target, .. } | ScalarDifference::ApproxNumber { target, .. } => {
+                Value::Number(target.clone())
+            }
+            ScalarDifference::DateTime { target, .. } => target.clone().into_owned(),
+        }
+    }
+}
+
+/// Escapes `~` and `/` in a JSON Pointer reference token.
+pub(crate) fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Reverses [`escape_pointer_token`], or returns `None` if `token` contains an invalid escape.
+pub(crate) fn unescape_pointer_token(token: &str) -> Option<String> {
+    let mut res = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '~' => match chars.next()? {
+                '0' => res.push('~'),
+                '1' => res.push('/'),
+                _ => return None,
+            },
+            c => res.push(c),
+        }
+    }
+    Some(res)
+}
+
+fn with_token<R>(pointer: &mut String, token: &str, f: impl FnOnce(&mut String) -> R) -> R {
+    let len = pointer.len();
+    pointer.push('/');
+    pointer.push_str(token);
+    let res = f(pointer);
+    pointer.truncate(len);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{ArrayDiffMode, DiffBuilder};
+    use super::*;
+
+    #[test]
+    fn test_to_json_patch() {
+        let source = json!({
+            "name": "John",
+            "a/b": {"c~d": 1},
+            "removed": true,
+            "tags": ["a", "b", "c"],
+            "list": [1, 2, 3],
+        });
+        let target = json!({
+            "name": "Joe",
+            "a/b": {"c~d": "1"},
+            "added": null,
+            "tags": ["x", "a", "c"],
+            "list": [1, 5],
+        });
+
+        let patch = DiffBuilder::default()
+            .array_diff_mode_at("tags", ArrayDiffMode::Lcs)
+            .source(source)
+            .target(target)
+            .build()
+            .unwrap()
+            .json_patch();
+
+        assert_eq!(serde_json::to_value(patch).unwrap(), json!([
+            {"op": "replace", "path": "/a~1b/c~0d", "value": "1"},
+            {"op": "replace", "path": "/list/1", "value": 5},
+            {"op": "remove", "path": "/list/2"},
+            {"op": "replace", "path": "/name", "value": "Joe"},
+            {"op": "remove", "path": "/removed"},
+            {"op": "add", "path": "/tags/0", "value": "x"},
+            {"op": "remove", "path": "/tags/2"},
+            {"op": "add", "path": "/added", "value": null},
+        ]));
+    }
+
+    #[test]
+    fn apply_difference() {
+        let source = json!({
+            "name": "John",
+            "a/b": {"c~d": 1},
+            "removed": true,
+            "tags": ["a", "b", "c", "d"],
+            "list": [1, 2, 3],
+            "items": [{"id": 1, "v": 1}, {"id": 2, "v": 2}, {"id": 3, "v": 3}],
+            "short": [1],
+        });
+        let target = json!({
+            "name": "Joe",
+            "a/b": {"c~d": "1"},
+            "added": null,
+            "tags": ["x", "a", "c", "y"],
+            "list": [1, 5],
+            "items": [{"id": 3, "v": 3}, {"id": 1, "v": 10}, {"id": 4, "v": 4}],
+            "short": [1, 2, 3],
+        });
+
+        for mode in [ArrayDiffMode::Index, ArrayDiffMode::Lcs, ArrayDiffMode::Unordered] {
+            let diff = DiffBuilder::default()
+                .array_diff_mode(mode)
+                .source(source.clone())
+                .target(target.clone())
+                .build()
+                .unwrap();
+            let diff = diff.compare().unwrap();
+
+            assert_eq!(diff.apply(&source).unwrap(), target, "mode {:?}", mode);
+        }
+
+        let diff = DiffBuilder::default()
+            .array_key("items", "id")
+            .source(source.clone())
+            .target(target.clone())
+            .build()
+            .unwrap();
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.apply(&source).unwrap(), target);
+
+        let source = json!({"a": [{"id": 1}, {"id": 2}, {"id": 3}, {"id": 4}, 5]});
+        let target = json!({"a": [6, {"id": 4}, {"id": 2, "v": 1}, {"id": 1}]});
+        let diff = DiffBuilder::default()
+            .array_key("a", "id")
+            .source(source.clone())
+            .target(target.clone())
+            .build()
+            .unwrap();
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.apply(&source).unwrap(), target);
+        assert_eq!(diff.to_merge_patch(&source).unwrap(), target);
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let source = json!({"a": [1, 2], "b": {"c": 1}});
+        let patch: Vec<PatchOperation> = serde_json::from_value(json!([
+            {"op": "test", "path": "/b/c", "value": 1},
+            {"op": "add", "path": "/a/-", "value": 3},
+            {"op": "move", "from": "/b/c", "path": "/d"},
+            {"op": "copy", "from": "/a", "path": "/b/a"},
+        ])).unwrap();
+
+        assert_eq!(
+            apply_patch(&source, &patch, ApplyMode::Strict).unwrap(),
+            json!({"a": [1, 2, 3], "b": {"a": [1, 2, 3]}, "d": 1}),
+        );
+
+        let failing_test = [PatchOperation::Test { path: "/b/c".to_string(), value: json!(2) }];
+        assert_eq!(
+            apply_patch(&source, &failing_test, ApplyMode::Strict),
+            Err(PatchError::TestFailed { path: "/b/c".to_string(), expected: json!(2), actual: json!(1) }),
+        );
+        assert_eq!(apply_patch(&source, &failing_test, ApplyMode::Lenient), Ok(source.clone()));
+        let missing_test = [PatchOperation::Test { path: "/x/y".to_string(), value: json!(2) }];
+        assert_eq!(apply_patch(&source, &missing_test, ApplyMode::Lenient), Ok(source.clone()));
+        assert_eq!(apply_patch(&source, &missing_test, ApplyMode::Strict), Err(PatchError::PathNotFound("/x/y".to_string())));
+
+        let remove = |path: &str| [PatchOperation::Remove { path: path.to_string() }];
+        assert_eq!(apply_patch(&source, &remove("/x"), ApplyMode::Strict), Err(PatchError::PathNotFound("/x".to_string())));
+        assert_eq!(apply_patch(&source, &remove("/a/5"), ApplyMode::Strict), Err(PatchError::InvalidIndex("/a/5".to_string())));
+        assert_eq!(apply_patch(&source, &remove("/a/01"), ApplyMode::Strict), Err(PatchError::InvalidIndex("/a/01".to_string())));
+        assert_eq!(apply_patch(&source, &remove("a"), ApplyMode::Strict), Err(PatchError::InvalidPointer("a".to_string())));
+        assert_eq!(apply_patch(&source, &remove("/b~2"), ApplyMode::Strict), Err(PatchError::InvalidPointer("/b~2".to_string())));
+
+        let into_child = [PatchOperation::Move { from: "/b".to_string(), path: "/b/c/d".to_string() }];
+        assert!(matches!(apply_patch(&source, &into_child, ApplyMode::Strict), Err(PatchError::MoveIntoChild { .. })));
+    }
+}
@@ -0,0 +1,175 @@
+use serde_json::Value;
+
+use crate::{ArrayDiffMode, DateTimeOptions, Error, Path, PathError, Tolerance};
+
+/// A compiled JSONPath expression.
+#[cfg(feature = "json_path")]
+type Selector = serde_json_path::JsonPath;
+
+/// Without the `json_path` feature, no selector can be parsed.
+#[cfg(not(feature = "json_path"))]
+#[derive(Clone, Debug)]
+enum Selector {}
+
+/// An [RFC 9535](https://www.rfc-editor.org/rfc/rfc9535) JSONPath expression. The nodes it
+/// selects depend on the compared documents, so it is resolved into concrete paths when the
+/// comparison starts.
+///
+/// A node selected in either document selects the same path in both, e.g. a filter that selects
+/// the first element of a `source` array also selects the first element of the `target` array.
+#[derive(Clone, Debug)]
+pub(crate) struct JsonPath {
+    selector: Selector,
+}
+
+/// A rule for the nodes selected by a JSONPath expression.
+#[derive(Clone, Debug)]
+pub(crate) struct JsonPathRule {
+    /// The expression as written
+    pub(crate) expression: String,
+    pub(crate) path: JsonPath,
+    pub(crate) rule: Rule,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Rule {
+    Ignore { ignore_missing: bool },
+    ArrayMode(ArrayDiffMode),
+    ArrayKeys(Vec<String>),
+    Tolerance(Tolerance),
+    DateTime(DateTimeOptions),
+}
+
+/// Whether `path` is a JSONPath expression rather than a dotted path, whose first key may start
+/// with `$`, e.g. `$schema`.
+pub(crate) fn is_json_path(path: &str) -> bool {
+    path == "$" || path.starts_with("$.") || path.starts_with("$[")
+}
+
+impl JsonPathRule {
+    pub(crate) fn parse(path: &str, rule: Rule) -> Result<Self, Error> {
+        JsonPath::parse(path).map(|json_path| JsonPathRule { expression: path.to_string(), path: json_path, rule })
+    }
+}
+
+impl JsonPath {
+    #[cfg(feature = "json_path")]
+    pub(crate) fn parse(path: &str) -> Result<Self, Error> {
+        let selector = serde_json_path::JsonPath::parse(path).map_err(|err| Error::InvalidPath {
+            path: path.to_string(),
+            source: PathError::new(err.position().saturating_sub(1), err.message()),
+        })?;
+        Ok(JsonPath { selector })
+    }
+
+    #[cfg(not(feature = "json_path"))]
+    pub(crate) fn parse(path: &str) -> Result<Self, Error> {
+        Err(Error::InvalidPath {
+            path: path.to_string(),
+            source: PathError::new(0, "JSONPath expressions require the `json_path` feature"),
+        })
+    }
+
+    /// The paths of the nodes selected in `source` or in `target`.
+    #[cfg(feature = "json_path")]
+    pub(crate) fn resolve(&self, source: &Value, target: &Value) -> Vec<Path> {
+        use std::collections::HashSet;
+
+        use crate::{ArrayIndex, PathElement};
+
+        let mut seen = HashSet::new();
+        let mut paths: Vec<Path> = Vec::new();
+        for document in [source, target] {
+            for location in self.selector.query_located(document).locations() {
+                if !seen.insert(location.to_string()) {
+                    continue;
+                }
+                let path: Path = location
+                    .iter()
+                    .map(|element| match element {
+                        serde_json_path::PathElement::Name(name) => PathElement::Key(name.to_string()),
+                        serde_json_path::PathElement::Index(index) => PathElement::ArrayIndex(ArrayIndex::Index(*index)),
+                    })
+                    .collect::<Vec<_>>()
+                    .into();
+                paths.push(path);
+            }
+        }
+        paths
+    }
+
+    #[cfg(not(feature = "json_path"))]
+    pub(crate) fn resolve(&self, _source: &Value, _target: &Value) -> Vec<Path> {
+        match self.selector {}
+    }
+}
+
+#[cfg(all(test, feature = "json_path"))]
+mod tests {
+    use serde_json::json;
+    use crate::{ArrayDiffMode, DiffBuilder, Error, Tolerance};
+
+    #[test]
+    fn json_path_rules() {
+        let obj1 = json!({
+            "items": [
+                {"type": "internal", "token": "a"},
+                {"type": "public", "name": "x", "meta": {"updated_at": 1}},
+            ],
+            "tags": ["a", "b"],
+        });
+
+        let obj2 = json!({
+            "items": [
+                {"type": "internal", "token": "b"},
+                {"type": "public", "name": "x", "meta": {"updated_at": 2}},
+            ],
+            "tags": ["b", "a"],
+        });
+
+        let diff = DiffBuilder::default()
+            .ignore_path("$.items[?@.type == 'internal']")
+            .ignore_path("$..updated_at")
+            .array_diff_mode_at("$.tags", ArrayDiffMode::Unordered)
+            .source(obj1.clone()).target(obj2.clone()).build().unwrap();
+
+        let diff = diff.compare();
+
+        assert!(diff.is_none(), "diff should be None, but got: {:?}", diff);
+
+        let diff = DiffBuilder::default()
+            .ignore_path("$.items[?@.type == 'public']")
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), "items.[0].token: - \"a\" / + \"b\"\ntags.[0]: - \"a\" / + \"b\"\ntags.[1]: - \"b\" / + \"a\"\n");
+
+        // The filter selects the first element in `source`, and so the first element in `target` too.
+        let diff = DiffBuilder::default()
+            .ignore_path("$.items[?@.type == 'internal']")
+            .source(json!({"items": [{"type": "internal"}, {"type": "public"}]}))
+            .target(json!({"items": [{"type": "public"}, {"type": "public", "name": "x"}]}))
+            .build()
+            .unwrap();
+
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), "items.[1].name: + \"x\"\n");
+
+        let diff = DiffBuilder::default()
+            .number_tolerance_at("$..price", Tolerance::Absolute(0.01))
+            .source(json!({"item": {"price": 1.0}}))
+            .target(json!({"item": {"price": 1.5}}))
+            .build()
+            .unwrap();
+
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), "item.price: - 1.0 / + 1.5 (off by 0.5 or 33.3%, tolerance 0.01 for `$..price`)\n");
+
+        let err = DiffBuilder::default().ignore_path("$.items[?").source(json!(null)).target(json!(null)).build().unwrap_err();
+
+        assert!(matches!(err, Error::InvalidPath { .. }), "expected an invalid path, but got: {:?}", err);
+    }
+}
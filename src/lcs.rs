@@ -0,0 +1,86 @@
+/// Sequences whose parts between their common prefix and suffix have more elements than this,
+/// multiplied, are not aligned, as that takes quadratic time and memory.
+pub(crate) const MAX_CELLS: usize = 1 << 22;
+
+/// Returns the index pairs of a longest common subsequence of two sequences, in ascending order,
+/// or `None` if they are too long to align, see [`MAX_CELLS`].
+///
+/// `eq(i, j)` reports whether the `i`th source element is equal to the `j`th target element.
+/// Common prefixes and suffixes are matched directly, the rest takes O(n * m) time and memory.
+pub(crate) fn longest_common_subsequence(
+    source_len: usize,
+    target_len: usize,
+    mut eq: impl FnMut(usize, usize) -> bool,
+) -> Option<Vec<(usize, usize)>> {
+    let shorter = source_len.min(target_len);
+    let prefix = (0..shorter).take_while(|&i| eq(i, i)).count();
+    let suffix = (0..shorter - prefix)
+        .take_while(|&k| eq(source_len - 1 - k, target_len - 1 - k))
+        .count();
+
+    let n = source_len - prefix - suffix;
+    let m = target_len - prefix - suffix;
+    if n.saturating_mul(m) > MAX_CELLS {
+        return None;
+    }
+
+    // `lengths[i * (m + 1) + j]` is the length of the LCS of the middle parts starting at `i` and `j`.
+    let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+    let mut equal = vec![false; n * m];
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[at(i, j)] = if eq(prefix + i, prefix + j) {
+                equal[i * m + j] = true;
+                lengths[at(i + 1, j + 1)] + 1
+            } else {
+                lengths[at(i + 1, j)].max(lengths[at(i, j + 1)])
+            };
+        }
+    }
+
+    let mut pairs: Vec<_> = (0..prefix).map(|i| (i, i)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if equal[i * m + j] {
+            pairs.push((prefix + i, prefix + j));
+            i += 1;
+            j += 1;
+        } else if lengths[at(i + 1, j)] >= lengths[at(i, j + 1)] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs.extend((source_len - suffix..source_len).zip(target_len - suffix..target_len));
+
+    Some(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lcs(source: &str, target: &str) -> String {
+        let (source, target): (Vec<_>, Vec<_>) = (source.chars().collect(), target.chars().collect());
+        longest_common_subsequence(source.len(), target.len(), |i, j| source[i] == target[j])
+            .unwrap()
+            .into_iter()
+            .map(|(i, _)| source[i])
+            .collect()
+    }
+
+    #[test]
+    fn test_longest_common_subsequence() {
+        assert_eq!(lcs("abcde", "abcde"), "abcde");
+        assert_eq!(lcs("abcde", "xabcde"), "abcde");
+        assert_eq!(lcs("abcbdab", "bdcaba").len(), 4);
+        assert_eq!(lcs("abc", "xyz"), "");
+        assert_eq!(lcs("", "abc"), "");
+        assert_eq!(lcs("axbyc", "abc"), "abc");
+
+        let long = "ab".repeat(3000);
+        assert_eq!(lcs(&format!("x{long}x"), &format!("x{long}x")), format!("x{long}x"));
+        assert!(longest_common_subsequence(3000, 3000, |_, _| false).is_none());
+    }
+}
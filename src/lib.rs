@@ -1,26 +1,63 @@
 #![doc = include_str!("../README.md")]
 
+mod assert;
+mod changes;
+mod comparator;
+mod datetime;
 mod element_path_parser;
-
+mod error;
+mod json_patch;
+mod json_path;
+mod lcs;
+mod merge_patch;
+mod number;
+mod path_matcher;
+mod pretty_diff;
+mod render;
+mod text_diff;
+mod visitor;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::iter::once;
 use std::ops::{Deref, DerefMut};
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
-use approx::relative_eq;
-use chrono::{DateTime};
+use approx::{relative_eq, ulps_eq};
+use chrono::TimeDelta;
 use derive_builder::Builder;
 use serde::{ser::SerializeMap, Serialize};
 use serde_json::Number;
+use crate::comparator::{ComparatorRule, When};
 use crate::element_path_parser::parse_element_path;
+use crate::json_patch::{escape_pointer_token, unescape_pointer_token};
+use crate::json_path::{is_json_path, JsonPath, JsonPathRule, Rule};
+use crate::path_matcher::{array_path, PathMatcher, Pattern};
+
+#[doc(hidden)]
+pub use crate::assert::assert_json as __assert_json;
+pub use crate::changes::Change;
+pub use crate::comparator::{Comparator, Outcome};
+pub use crate::datetime::{DateTimeFormat, DateTimeOptions};
+pub use crate::error::{Error, PathError};
+pub use crate::json_patch::{apply_patch, ApplyMode, PatchError, PatchOperation};
+pub use crate::merge_patch::{apply_merge_patch, MergePatchError};
+pub use crate::render::ColorChoice;
+pub use crate::text_diff::{TextEdit, TextGranularity};
+pub use crate::visitor::{walk, DifferenceVisitor};
 
 #[derive(Debug, Serialize)]
 #[serde(tag = "entry_difference", rename_all = "snake_case")]
-pub enum EntryDifference {
+pub enum EntryDifference<'a> {
     /// An entry from `target` that `source` is missing
-    Missing { value: serde_json::Value },
+    Missing { value: Cow<'a, serde_json::Value> },
     /// An entry that `source` has, and `target` doesn't
-    Extra { value: serde_json::Value },
+    Extra { value: Cow<'a, serde_json::Value> },
     /// The entry exists in both JSONs, but the values are different
-    Value { value_diff: Difference },
+    Value { value_diff: Difference<'a> },
 }
 
 #[derive(Debug)]
@@ -41,28 +78,131 @@ impl<K: Serialize, V: Serialize> Serialize for DumbMap<K, V> {
 
 #[derive(Debug, Serialize)]
 #[serde(tag = "array_difference", rename_all = "snake_case")]
-pub enum ArrayDifference {
+pub enum ArrayDifference<'a> {
     /// `source` and `target` are the same length, but some values of the same indices are different
     PairsOnly {
         /// differing pairs that appear in the overlapping indices of `source` and `target`
-        different_pairs: DumbMap<usize, Difference>,
+        different_pairs: DumbMap<usize, Difference<'a>>,
     },
     /// `source` is shorter than `target`
     Shorter {
         /// differing pairs that appear in the overlapping indices of `source` and `target`
-        different_pairs: Option<DumbMap<usize, Difference>>,
+        different_pairs: Option<DumbMap<usize, Difference<'a>>>,
         /// elements missing in `source` that appear in `target`
-        missing_elements: Vec<serde_json::Value>,
+        missing_elements: Vec<Cow<'a, serde_json::Value>>,
+        /// The length of `source`, which is the index of the first missing element in `target`
+        source_length: usize,
     },
     /// `source` is longer than `target`
     Longer {
         /// differing pairs that appear in the overlapping indices of `source` and `target`
-        different_pairs: Option<DumbMap<usize, Difference>>,
+        different_pairs: Option<DumbMap<usize, Difference<'a>>>,
         /// The amount of extra elements `source` has that `target` does not
         extra_length: usize,
+        /// The length of `target`, which is the index of the first extra element in `source`
+        target_length: usize,
+    },
+    /// `source` and `target` were aligned by their longest common subsequence
+    EditScript {
+        /// edits that turn `source` into `target`, in order
+        edits: Vec<ArrayEdit<'a>>,
+    },
+    /// Elements of `source` and `target` were paired by their key fields or by their values,
+    /// regardless of their order
+    Matched {
+        /// pairs that differ, followed by unmatched elements of `source` and `target`
+        edits: Vec<ArrayEdit<'a>>,
+        /// equal pairs at different indices, as `(source_index, target_index)`
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        moved: Vec<(usize, usize)>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "edit", rename_all = "snake_case")]
+pub enum ArrayEdit<'a> {
+    /// An element of `target` at `index` that `source` doesn't have
+    Insert { index: usize, value: Cow<'a, serde_json::Value> },
+    /// An element of `source` at `index` that `target` doesn't have
+    Delete { index: usize, value: Cow<'a, serde_json::Value> },
+    /// An element that was changed in place
+    Modify {
+        source_index: usize,
+        target_index: usize,
+        value_diff: Difference<'a>,
     },
 }
 
+/// How the elements of two arrays are paired up for comparison.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArrayDiffMode {
+    /// Elements are compared index by index.
+    #[default]
+    Index,
+    /// Elements are aligned by the longest common subsequence of their exactly equal elements, so
+    /// insertions and deletions are reported at their real positions, and the elements in between
+    /// are paired up and compared. Takes quadratic time in the worst case, so arrays that differ in
+    /// too many elements are compared by index instead.
+    Lcs,
+    /// Arrays are compared as multisets: equal elements are paired regardless of their position,
+    /// and the remaining objects and arrays are paired with their closest match.
+    Unordered,
+}
+
+/// Whether one of the documents only has to contain the other.
+///
+/// Differences computed with a mode other than [`CompareMode::Exact`] leave out changes, so
+/// patches generated from them don't necessarily turn `source` into `target`.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CompareMode {
+    /// Both documents must be equal.
+    #[default]
+    Exact,
+    /// `source` must contain `target`: object members and array elements that only `source` has
+    /// are not reported. Arrays must contain the elements of `target` in the same order, but not
+    /// necessarily next to each other; combine with [`ArrayDiffMode::Unordered`] to allow any order.
+    Contains,
+    /// `target` must contain `source`: object members and array elements that only `target` has
+    /// are not reported.
+    ContainedIn,
+}
+
+/// How far apart two numbers may be and still be equal.
+///
+/// Numbers are compared as `f64`s, whether they are integers or floats.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tolerance {
+    /// At most this far apart, e.g. `0.01` for prices in cents.
+    Absolute(f64),
+    /// At most this fraction of the larger magnitude apart, e.g. `0.01` for 1%.
+    Relative(f64),
+    /// At most this many representable `f64`s apart.
+    Ulps(u32),
+}
+
+impl Tolerance {
+    fn validate(self, option: &'static str) -> Result<Self, Error> {
+        match self {
+            Tolerance::Absolute(value) | Tolerance::Relative(value) if !value.is_finite() || value < 0.0 => {
+                Err(Error::InvalidOption {
+                    option,
+                    reason: format!("expected a finite, non-negative tolerance, got {value}"),
+                })
+            }
+            _ => Ok(self),
+        }
+    }
+
+    fn eq(self, source: f64, target: f64) -> bool {
+        match self {
+            Tolerance::Absolute(epsilon) => (source - target).abs() <= epsilon,
+            Tolerance::Relative(max_relative) => relative_eq!(source, target, epsilon = 0.0, max_relative = max_relative),
+            Tolerance::Ulps(max_ulps) => ulps_eq!(source, target, epsilon = 0.0, max_ulps = max_ulps),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Type {
@@ -74,83 +214,598 @@ pub enum Type {
     Number,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 #[serde(untagged)]
-pub enum ScalarDifference {
+pub enum ScalarDifference<'a> {
     Bool {
         source: bool,
         target: bool,
     },
     String {
-        source: String,
-        target: String,
+        source: Cow<'a, str>,
+        target: Cow<'a, str>,
+        /// An inline diff of the strings, if [`DiffBuilder::text_diff`] is set and they are long
+        /// enough
+        #[serde(skip_serializing_if = "Option::is_none")]
+        text_diff: Option<Vec<TextEdit<'a>>>,
     },
     Number {
         source: serde_json::Number,
         target: serde_json::Number,
     },
+    /// Numbers that are further apart than the tolerance at their path
+    ApproxNumber {
+        source: serde_json::Number,
+        target: serde_json::Number,
+        /// `target - source`
+        delta: f64,
+        /// `|target - source|` relative to the larger magnitude of both
+        relative_error: f64,
+        tolerance: Tolerance,
+        /// The path the tolerance was set for with [`DiffBuilder::number_tolerance_at`], or `None`
+        /// if it's [`DiffBuilder::approx_float_eq_epsilon`]
+        tolerance_path: Option<String>,
+    },
+    /// Datetimes that are further apart than the tolerance at their path
+    DateTime {
+        source: Cow<'a, serde_json::Value>,
+        target: Cow<'a, serde_json::Value>,
+        /// How much later `target` is than `source`, serialized in seconds
+        #[serde(serialize_with = "datetime::serialize_delta")]
+        delta: TimeDelta,
+        /// Serialized in seconds
+        #[serde(serialize_with = "datetime::serialize_duration")]
+        tolerance: Duration,
+        /// Whether only the dates were compared, so `delta` is a whole number of days
+        date_only: bool,
+    },
 }
 
+/// The difference between two JSON values.
+///
+/// Values and strings are borrowed from the compared documents where possible;
+/// use [`Difference::into_owned`] to detach it from them.
 #[derive(Debug, Serialize)]
 #[serde(tag = "difference_of", rename_all = "snake_case")]
-pub enum Difference {
-    Scalar(ScalarDifference),
+pub enum Difference<'a> {
+    Scalar(ScalarDifference<'a>),
     Type {
         source_type: Type,
-        source_value: serde_json::Value,
+        source_value: Cow<'a, serde_json::Value>,
         target_type: Type,
-        target_value: serde_json::Value,
+        target_value: Cow<'a, serde_json::Value>,
     },
-    Array(ArrayDifference),
+    /// Values that a [`Comparator`] found different as a whole
+    Custom {
+        source_value: Cow<'a, serde_json::Value>,
+        target_value: Cow<'a, serde_json::Value>,
+        /// The path the comparator was registered for, or `None` if it was registered with a predicate
+        comparator: Option<String>,
+    },
+    Array(ArrayDifference<'a>),
     Object {
-        different_entries: DumbMap<String, EntryDifference>,
+        different_entries: DumbMap<Cow<'a, str>, EntryDifference<'a>>,
     },
 }
 
-
 #[derive(Default, Builder, Debug)]
-pub struct Diff {
-    #[builder(default = vec![])]
+#[builder(build_fn(validate = "Self::validate", error = "Error"))]
+pub struct Diff<'a> {
+    #[builder(setter(custom), field(ty = "Vec<Result<IgnorePath, Error>>", build = "collect_rules(&self.ignore_paths)?"))]
     ignore_paths: Vec<IgnorePath>,
 
-    #[builder(setter(skip))]
-    #[builder(default = vec![].into())]
-    curr_path: Path,
-
     /// If true arrays with a length of zero will be equal, regardless of whether they are nil.
     #[builder(default = false)]
     equate_empty_arrays: bool,
 
+    /// Whether an integer equals a float with the same value, e.g. `1` and `1.0`. If false, they
+    /// are different even within a tolerance.
+    #[builder(default = true)]
+    equate_integers_and_floats: bool,
+
+    /// Numbers that are at most this far apart are equal if at least one of them is a float,
+    /// unless a [`DiffBuilder::number_tolerance_at`] rule applies to them.
     #[builder(default = 0.0)]
     approx_float_eq_epsilon: f64,
 
+    /// RFC 3339 datetimes that are at most this far apart are equal, unless a
+    /// [`DiffBuilder::date_time_at`] rule applies to them.
     #[builder(default = Duration::from_millis(0))]
     approx_date_time_eq_duration: Duration,
 
-    source: serde_json::Value,
-    target: serde_json::Value,
+    #[builder(default)]
+    array_diff_mode: ArrayDiffMode,
+
+    #[builder(default)]
+    compare_mode: CompareMode,
+
+    /// Attach an inline diff in these units to differing strings, see
+    /// [`ScalarDifference::String`].
+    #[builder(default, setter(strip_option))]
+    text_diff: Option<TextGranularity>,
+
+    /// Strings shorter than this many characters are shown whole, without an inline diff.
+    #[builder(default = 40)]
+    text_diff_min_length: usize,
+
+    #[builder(setter(custom), field(ty = "Vec<Result<ArrayMode, Error>>", build = "collect_rules(&self.array_modes)?"))]
+    array_modes: Vec<ArrayMode>,
+
+    #[builder(setter(custom), field(ty = "Vec<Result<ArrayKey, Error>>", build = "collect_rules(&self.keyed_arrays)?"))]
+    keyed_arrays: Vec<ArrayKey>,
+
+    #[builder(setter(custom), field(ty = "Vec<Result<NumberTolerance, Error>>", build = "collect_rules(&self.number_tolerances)?"))]
+    number_tolerances: Vec<NumberTolerance>,
+
+    #[builder(setter(custom), field(ty = "Vec<Result<DateTimeRule, Error>>", build = "collect_rules(&self.date_times)?"))]
+    date_times: Vec<DateTimeRule>,
+
+    #[builder(setter(custom), field(ty = "Vec<Result<ComparatorRule, Error>>", build = "collect_rules(&self.comparators)?"))]
+    comparators: Vec<ComparatorRule>,
+
+    /// Rules given as JSONPath expressions, for any of the above
+    #[builder(setter(custom), field(ty = "Vec<Result<JsonPathRule, Error>>", build = "collect_rules(&self.json_path_rules)?"))]
+    json_path_rules: Vec<JsonPathRule>,
+
+    #[builder(setter(custom), field(ty = "Option<Result<Document<'a>, Error>>", build = "document(&self.source, \"source\")?"))]
+    source: Document<'a>,
+    #[builder(setter(custom), field(ty = "Option<Result<Document<'a>, Error>>", build = "document(&self.target, \"target\")?"))]
+    target: Document<'a>,
+}
+
+/// A document to compare, either borrowed or shared, so building a [`Diff`] doesn't copy it.
+#[derive(Clone, Debug)]
+enum Document<'a> {
+    Borrowed(&'a serde_json::Value),
+    Owned(Arc<serde_json::Value>),
+}
+
+impl Default for Document<'_> {
+    fn default() -> Self {
+        Document::Owned(Arc::default())
+    }
+}
+
+impl Deref for Document<'_> {
+    type Target = serde_json::Value;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Document::Borrowed(value) => value,
+            Document::Owned(value) => value,
+        }
+    }
 }
 
-impl DiffBuilder {
+impl<'a> DiffBuilder<'a> {
+    pub fn source(&mut self, source: serde_json::Value) -> &mut Self {
+        self.source = Some(Ok(Document::Owned(Arc::new(source))));
+        self
+    }
+
+    pub fn target(&mut self, target: serde_json::Value) -> &mut Self {
+        self.target = Some(Ok(Document::Owned(Arc::new(target))));
+        self
+    }
+
+    /// Like [`DiffBuilder::source`], but borrows the document instead of taking it.
+    pub fn source_ref(&mut self, source: &'a serde_json::Value) -> &mut Self {
+        self.source = Some(Ok(Document::Borrowed(source)));
+        self
+    }
+
+    /// Like [`DiffBuilder::target`], but borrows the document instead of taking it.
+    pub fn target_ref(&mut self, target: &'a serde_json::Value) -> &mut Self {
+        self.target = Some(Ok(Document::Borrowed(target)));
+        self
+    }
+
+    /// Sets the source to any serializable value, e.g. a struct whose field names become path keys.
+    pub fn source_serializable<T: Serialize + ?Sized>(&mut self, source: &T) -> &mut Self {
+        self.source = Some(to_document(source, "source"));
+        self
+    }
+
+    /// Sets the target to any serializable value, e.g. a struct whose field names become path keys.
+    pub fn target_serializable<T: Serialize + ?Sized>(&mut self, target: &T) -> &mut Self {
+        self.target = Some(to_document(target, "target"));
+        self
+    }
+
+    /// Ignores the object member or array element at `path`.
+    ///
+    /// `path` is a dotted path, e.g. `items.[_].id`, a JSON Pointer, e.g. `/items/0/id`, or, with the
+    /// `json_path` feature, a JSONPath expression starting with `$.` or `$[` and evaluated against
+    /// both documents, e.g. `$.items[?@.type == 'internal']`.
+    /// Ignored array elements are considered equal to the element they are paired with.
     pub fn ignore_path(&mut self, path: &str) -> &mut Self {
         self.ignore_path_with_missing(path, false)
     }
 
     pub fn ignore_path_with_missing(&mut self, path: &str, ignore_missing: bool) -> &mut Self {
-        if let Ok(elements) = Path::from_str(path) {
-            self.ignore_paths.get_or_insert_with(Vec::new).push(IgnorePath(elements, ignore_missing));
+        match is_json_path(path) {
+            true => self.json_path_rules.push(JsonPathRule::parse(path, Rule::Ignore { ignore_missing })),
+            false => self.ignore_paths.push(parse_path(path).map(|path| IgnorePath(path, ignore_missing))),
+        }
+        self
+    }
+
+    /// Overrides [`DiffBuilder::array_diff_mode`] for the array at `path`.
+    ///
+    /// `path` may name either the array, e.g. `tags`, or its elements, e.g. `tags.[_]`.
+    /// A JSONPath expression must select the array itself, e.g. `$.tags`.
+    pub fn array_diff_mode_at(&mut self, path: &str, mode: ArrayDiffMode) -> &mut Self {
+        match is_json_path(path) {
+            true => self.json_path_rules.push(JsonPathRule::parse(path, Rule::ArrayMode(mode))),
+            false => self.array_modes.push(parse_path(path).map(|path| ArrayMode(path, mode))),
+        }
+        self
+    }
+
+    /// Compares the array at `path` regardless of the order of its elements.
+    pub fn unordered_array(&mut self, path: &str) -> &mut Self {
+        self.array_diff_mode_at(path, ArrayDiffMode::Unordered)
+    }
+
+    /// Pairs the elements of the array at `path` by the value of their `key` field instead of their index.
+    ///
+    /// `path` may name either the array, e.g. `items`, or its elements, e.g. `items.[_]`.
+    /// A JSONPath expression must select the array itself, e.g. `$.items`.
+    /// Elements without the `key` field, including non-objects, are paired by their order among such elements.
+    pub fn array_key(&mut self, path: &str, key: &str) -> &mut Self {
+        self.array_keys(path, &[key])
+    }
+
+    /// Like [`DiffBuilder::array_key`], but elements are paired by the values of all `keys`.
+    pub fn array_keys(&mut self, path: &str, keys: &[&str]) -> &mut Self {
+        let keys: Vec<_> = keys.iter().map(|key| key.to_string()).collect();
+        if keys.is_empty() {
+            self.keyed_arrays.push(Err(Error::InvalidOption {
+                option: "array_keys",
+                reason: format!("no key fields given for `{path}`"),
+            }));
+        } else if is_json_path(path) {
+            self.json_path_rules.push(JsonPathRule::parse(path, Rule::ArrayKeys(keys)));
+        } else {
+            self.keyed_arrays.push(parse_path(path).map(|path| ArrayKey(path, keys)));
+        }
+        self
+    }
+
+    /// Overrides [`DiffBuilder::approx_float_eq_epsilon`] for the numbers at `path`, e.g.
+    /// `prices.[_].amount` or `metrics.*`.
+    pub fn number_tolerance_at(&mut self, path: &str, tolerance: Tolerance) -> &mut Self {
+        let tolerance = tolerance.validate("number_tolerance_at");
+        match (is_json_path(path), tolerance) {
+            (_, Err(err)) => self.number_tolerances.push(Err(err)),
+            (true, Ok(tolerance)) => self.json_path_rules.push(JsonPathRule::parse(path, Rule::Tolerance(tolerance))),
+            (false, Ok(tolerance)) => {
+                self.number_tolerances.push(parse_path(path).map(|path| NumberTolerance(path, tolerance)))
+            }
+        }
+        self
+    }
+
+    /// Compares the values at `path` as datetimes if both of them parse, overriding
+    /// [`DiffBuilder::approx_date_time_eq_duration`].
+    pub fn date_time_at(&mut self, path: &str, options: DateTimeOptions) -> &mut Self {
+        if options.formats.is_empty() {
+            self.date_times.push(Err(Error::InvalidOption {
+                option: "date_time_at",
+                reason: format!("no datetime formats given for `{path}`"),
+            }));
+        } else if options.date_only && !options.tolerance.as_nanos().is_multiple_of(86_400_000_000_000) {
+            self.date_times.push(Err(Error::InvalidOption {
+                option: "date_time_at",
+                reason: format!("the tolerance for the dates at `{path}` isn't a whole number of days"),
+            }));
+        } else if is_json_path(path) {
+            self.json_path_rules.push(JsonPathRule::parse(path, Rule::DateTime(options)));
+        } else {
+            self.date_times.push(parse_path(path).map(|path| DateTimeRule(path, options)));
         }
         self
     }
+
+    /// Compares the values at `path` with `comparator` before the built-in comparison.
+    ///
+    /// `path` takes the same forms as in [`DiffBuilder::ignore_path`].
+    pub fn comparator(&mut self, path: &str, comparator: impl Comparator + 'static) -> &mut Self {
+        let when = match is_json_path(path) {
+            true => JsonPath::parse(path).map(When::JsonPath),
+            false => parse_path(path).map(When::Path),
+        };
+        self.comparators.push(when.map(|when| ComparatorRule {
+            path: Some(path.to_string()),
+            when,
+            comparator: Arc::new(comparator),
+        }));
+        self
+    }
+
+    /// Like [`DiffBuilder::comparator`], but for the values at the paths that `predicate` accepts.
+    pub fn comparator_when(
+        &mut self,
+        predicate: impl Fn(&Path) -> bool + Send + Sync + 'static,
+        comparator: impl Comparator + 'static,
+    ) -> &mut Self {
+        self.comparators.push(Ok(ComparatorRule {
+            path: None,
+            when: When::Predicate(Arc::new(predicate)),
+            comparator: Arc::new(comparator),
+        }));
+        self
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        let ignore_paths = rule_refs(&self.ignore_paths)?;
+        let array_modes = rule_refs(&self.array_modes)?;
+        let keyed_arrays = rule_refs(&self.keyed_arrays)?;
+        let number_tolerances = rule_refs(&self.number_tolerances)?;
+        let date_times = rule_refs(&self.date_times)?;
+        rule_refs(&self.comparators)?;
+        rule_refs(&self.json_path_rules)?;
+
+        if let Some(epsilon) = self.approx_float_eq_epsilon {
+            if !epsilon.is_finite() || epsilon < 0.0 {
+                return Err(Error::InvalidOption {
+                    option: "approx_float_eq_epsilon",
+                    reason: format!("expected a finite, non-negative number, got {epsilon}"),
+                });
+            }
+        }
+        for (i, NumberTolerance(path, tolerance)) in number_tolerances.iter().enumerate() {
+            if number_tolerances[..i].iter().any(|other| other.0.is_same(path) && other.1 != *tolerance) {
+                return Err(conflict(path, "more than one number tolerance"));
+            }
+        }
+        for (i, DateTimeRule(path, options)) in date_times.iter().enumerate() {
+            if date_times[..i].iter().any(|other| other.0.is_same(path) && other.1 != *options) {
+                return Err(conflict(path, "more than one set of datetime options"));
+            }
+        }
+
+        for (i, IgnorePath(path, ignore_missing)) in ignore_paths.iter().enumerate() {
+            if ignore_paths[..i].iter().any(|other| other.0.is_same(path) && other.1 != *ignore_missing) {
+                return Err(conflict(path, "ignored both with and without missing values"));
+            }
+        }
+        for (i, ArrayMode(path, mode)) in array_modes.iter().enumerate() {
+            if array_modes[..i].iter().any(|other| array_path(&other.0).is_same(array_path(path)) && other.1 != *mode) {
+                return Err(conflict(path, "more than one array diff mode"));
+            }
+        }
+        for (i, ArrayKey(path, keys)) in keyed_arrays.iter().enumerate() {
+            if keyed_arrays[..i].iter().any(|other| array_path(&other.0).is_same(array_path(path)) && other.1 != *keys) {
+                return Err(conflict(path, "more than one set of array keys"));
+            }
+            if array_modes.iter().any(|other| array_path(&other.0).is_same(array_path(path))) {
+                return Err(conflict(path, "both array keys and an array diff mode"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Compares two serializable values, e.g. structs, with the default options.
+///
+/// Both values are serialized into [`serde_json::Value`]s first, so struct field names become
+/// path keys. Use [`DiffBuilder::source_serializable`] and [`DiffBuilder::target_serializable`]
+/// to compare them with other options.
+pub fn diff_serializable<S, T>(source: &S, target: &T) -> Result<Option<Difference<'static>>, Error>
+where
+    S: Serialize + ?Sized,
+    T: Serialize + ?Sized,
+{
+    let diff = DiffBuilder::default().source_serializable(source).target_serializable(target).build()?;
+    Ok(diff.compare().map(Difference::into_owned))
+}
+
+fn to_document<'a, T: Serialize + ?Sized>(value: &T, document: &'static str) -> Result<Document<'a>, Error> {
+    match serde_json::to_value(value) {
+        Ok(value) => Ok(Document::Owned(Arc::new(value))),
+        Err(err) => Err(Error::Serialize { document, reason: err.to_string() }),
+    }
+}
+
+fn document<'a>(value: &Option<Result<Document<'a>, Error>>, name: &'static str) -> Result<Document<'a>, Error> {
+    value.clone().unwrap_or(Err(Error::UninitializedField(name)))
+}
+
+/// Parses a dotted path, or a JSON Pointer if `path` starts with `/`.
+fn parse_path(path: &str) -> Result<Path, Error> {
+    let parsed = match path.starts_with('/') {
+        true => Path::from_json_pointer(path),
+        false => Path::from_str(path),
+    };
+    parsed.map_err(|source| Error::InvalidPath { path: path.to_string(), source })
+}
+
+fn collect_rules<T: Clone>(rules: &[Result<T, Error>]) -> Result<Vec<T>, Error> {
+    rules.iter().cloned().collect()
+}
+
+fn rule_refs<T>(rules: &[Result<T, Error>]) -> Result<Vec<&T>, Error> {
+    rules.iter().map(|rule| rule.as_ref().map_err(Clone::clone)).collect()
+}
+
+fn conflict(path: &Path, reason: &str) -> Error {
+    Error::ConflictingRules {
+        path: path.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+impl Diff<'_> {
+    pub fn compare(&self) -> Option<Difference<'_>> {
+        self.compare_values(&self.source, &self.target)
+    }
+
+    /// Compares `source` and `target` with the options of this diff, instead of its own documents.
+    pub fn compare_values<'a>(&self, source: &'a serde_json::Value, target: &'a serde_json::Value) -> Option<Difference<'a>> {
+        let mut diff = Comparison::new(self, source, target).values(source, target)?;
+        // Only the strings that are reported get an inline diff, not those compared while matching array elements.
+        if let Some(granularity) = self.text_diff {
+            diff.add_text_diffs(granularity, self.text_diff_min_length);
+        }
+        Some(diff)
+    }
+
+    pub fn source(&self) -> &serde_json::Value {
+        &self.source
+    }
+
+    pub fn target(&self) -> &serde_json::Value {
+        &self.target
+    }
+
+    /// Compares `source` and `target` and returns each change with its absolute path,
+    /// see [`Difference::changes`]. The changes own copies of their values; use
+    /// [`Difference::changes`] on the result of [`Diff::compare`] to borrow them instead.
+    pub fn changes(&self) -> Vec<(Path, Change<'static>)> {
+        let diff = self.compare();
+        let changes = diff.as_ref().map(Difference::changes).unwrap_or_default();
+        changes.into_iter().map(|(path, change)| (path, change.into_owned())).collect()
+    }
+
+    /// Compares `source` and `target` and returns the JSON Patch that turns `source` into `target`.
+    pub fn json_patch(&self) -> Vec<PatchOperation> {
+        self.compare().map_or_else(Vec::new, |diff| diff.to_json_patch(&self.source))
+    }
+
+    /// Compares `source` and `target` and returns the JSON Merge Patch that turns `source` into `target`.
+    pub fn merge_patch(&self) -> Result<serde_json::Value, MergePatchError> {
+        match self.compare() {
+            Some(diff) => diff.to_merge_patch(&self.source),
+            None if self.source.is_object() => Ok(serde_json::Value::Object(Default::default())),
+            None => Ok(self.source().clone()),
+        }
+    }
+}
+
+/// The state of a single comparison: the options of a [`Diff`] and the path being compared.
+struct Comparison<'d> {
+    diff: &'d Diff<'d>,
+    curr_path: Path,
+    /// Whether each ignore rule also ignores missing values
+    ignore_paths: PathMatcher<bool>,
+    array_modes: PathMatcher<ArrayDiffMode>,
+    array_keys: PathMatcher<&'d [String]>,
+    /// The tolerances, with the paths they were set for
+    number_tolerances: PathMatcher<(Tolerance, String)>,
+    date_times: PathMatcher<&'d DateTimeOptions>,
+    /// The datetime options for paths without their own
+    default_date_time: Option<DateTimeOptions>,
+    /// Indices of the comparators registered for a path
+    comparators: PathMatcher<usize>,
+}
+
+impl<'d> Deref for Comparison<'d> {
+    type Target = Diff<'d>;
+
+    fn deref(&self) -> &Self::Target {
+        self.diff
+    }
 }
 
-impl Diff {
-    fn arrays(
+impl<'d> Comparison<'d> {
+    fn new(diff: &'d Diff<'d>, source: &serde_json::Value, target: &serde_json::Value) -> Self {
+        let resolved: Vec<_> = diff
+            .json_path_rules
+            .iter()
+            .flat_map(|rule| rule.path.resolve(source, target).into_iter().map(move |path| (path, rule)))
+            .collect();
+
+        let ignore_paths = diff.ignore_paths.iter().map(|IgnorePath(path, ignore_missing)| (Pattern::from(path), *ignore_missing));
+        let array_modes = diff.array_modes.iter().map(|ArrayMode(path, mode)| (array_path(path), *mode));
+        let array_keys = diff.keyed_arrays.iter().map(|ArrayKey(path, keys)| (array_path(path), &keys[..]));
+        let number_tolerances =
+            diff.number_tolerances.iter().map(|NumberTolerance(path, tolerance)| (Pattern::from(path), (*tolerance, path.to_string())));
+        let date_times = diff.date_times.iter().map(|DateTimeRule(path, options)| (Pattern::from(path), options));
+        let comparator_paths: Vec<_> = diff
+            .comparators
+            .iter()
+            .enumerate()
+            .flat_map(|(i, rule)| match &rule.when {
+                When::Path(path) => vec![(path.clone(), i)],
+                When::JsonPath(path) => path.resolve(source, target).into_iter().map(|path| (path, i)).collect(),
+                When::Predicate(_) => Vec::new(),
+            })
+            .collect();
+        Comparison {
+            diff,
+            curr_path: Path::default(),
+            ignore_paths: PathMatcher::new(ignore_paths.chain(resolved.iter().filter_map(|(path, rule)| match &rule.rule {
+                Rule::Ignore { ignore_missing } => Some((Pattern::from(path), *ignore_missing)),
+                _ => None,
+            }))),
+            array_modes: PathMatcher::new(array_modes.chain(resolved.iter().filter_map(|(path, rule)| match &rule.rule {
+                Rule::ArrayMode(mode) => Some((Pattern::from(path), *mode)),
+                _ => None,
+            }))),
+            array_keys: PathMatcher::new(array_keys.chain(resolved.iter().filter_map(|(path, rule)| match &rule.rule {
+                Rule::ArrayKeys(keys) => Some((Pattern::from(path), &keys[..])),
+                _ => None,
+            }))),
+            number_tolerances: PathMatcher::new(number_tolerances.chain(resolved.iter().filter_map(|(path, rule)| match &rule.rule {
+                Rule::Tolerance(tolerance) => Some((Pattern::from(path), (*tolerance, rule.expression.clone()))),
+                _ => None,
+            }))),
+            date_times: PathMatcher::new(date_times.chain(resolved.iter().filter_map(|(path, rule)| match &rule.rule {
+                Rule::DateTime(options) => Some((Pattern::from(path), options)),
+                _ => None,
+            }))),
+            default_date_time: (!diff.approx_date_time_eq_duration.is_zero()).then(|| DateTimeOptions {
+                tolerance: diff.approx_date_time_eq_duration,
+                ..Default::default()
+            }),
+            comparators: PathMatcher::new(comparator_paths.iter().map(|(path, i)| (Pattern::from(path), *i))),
+        }
+    }
+
+    fn push(&mut self, element: PathElement) {
+        self.ignore_paths.push(&element);
+        self.array_modes.push(&element);
+        self.array_keys.push(&element);
+        self.number_tolerances.push(&element);
+        self.date_times.push(&element);
+        self.comparators.push(&element);
+        self.curr_path.push(element);
+    }
+
+    fn pop(&mut self) {
+        self.ignore_paths.pop();
+        self.array_modes.pop();
+        self.array_keys.pop();
+        self.number_tolerances.pop();
+        self.date_times.pop();
+        self.comparators.pop();
+        self.curr_path.pop();
+    }
+
+    fn arrays<'a>(
         &mut self,
-        source: Vec<serde_json::Value>,
-        target: Vec<serde_json::Value>,
-    ) -> Option<ArrayDifference> {
-        let different_pairs = self.compare_array_elements(&source, &target);
+        source: &'a [serde_json::Value],
+        target: &'a [serde_json::Value],
+    ) -> Option<ArrayDifference<'a>> {
+        if let Some(keys) = self.array_key() {
+            return self.array_matched_by_key(keys, source, target);
+        }
+        match self.array_mode() {
+            ArrayDiffMode::Index if self.compare_mode == CompareMode::Exact => {}
+            // In the contains modes, the elements of the contained array must appear in the same
+            // order in the other one, but not necessarily next to each other.
+            ArrayDiffMode::Index | ArrayDiffMode::Lcs => {
+                // Arrays too long to align are compared by index.
+                if let Some(common) = self.common_elements(source, target) {
+                    return self.array_edit_script(source, target, common);
+                }
+            }
+            ArrayDiffMode::Unordered => return self.array_unordered(source, target),
+        }
+
+        let different_pairs = self.compare_array_elements(source, target);
         let different_pairs = if different_pairs.is_empty() {
             None
         } else {
@@ -158,82 +813,248 @@ impl Diff {
         };
 
         match (source.len(), target.len()) {
-            (s, t) if s > t => Some(ArrayDifference::Longer {
+            (s, t) if s > t && self.compare_mode != CompareMode::Contains => Some(ArrayDifference::Longer {
                 different_pairs,
                 extra_length: s - t,
+                target_length: t,
             }),
-            (s, t) if s < t => Some(ArrayDifference::Shorter {
+            (s, t) if s < t && self.compare_mode != CompareMode::ContainedIn => Some(ArrayDifference::Shorter {
                 different_pairs,
-                missing_elements: target.into_iter().skip(s).collect(),
+                missing_elements: target[s..].iter().map(Cow::Borrowed).collect(),
+                source_length: s,
             }),
             _ => different_pairs.map(|pairs| ArrayDifference::PairsOnly { different_pairs: pairs }),
         }
     }
 
-    fn compare_array_elements(
+    /// The index pairs of a longest common subsequence of exactly equal elements, or `None` if the
+    /// arrays are too long to align. Elements are hashed first so most pairs are told apart cheaply,
+    /// and only the elements paired up between common ones are compared according to the options.
+    /// In the contains modes, elements that only contain one another are common too.
+    fn common_elements(&mut self, source: &[serde_json::Value], target: &[serde_json::Value]) -> Option<Vec<(usize, usize)>> {
+        let hash = |value| {
+            let mut hasher = DefaultHasher::new();
+            hash_value(value, &mut hasher);
+            hasher.finish()
+        };
+        let source_hashes: Vec<_> = source.iter().map(hash).collect();
+        let target_hashes: Vec<_> = target.iter().map(hash).collect();
+        let contains = self.compare_mode != CompareMode::Exact;
+        lcs::longest_common_subsequence(source.len(), target.len(), |i, j| {
+            (source_hashes[i] == target_hashes[j] && source[i] == target[j])
+                || (contains && self.array_element(i, &source[i], &target[j]).is_none())
+        })
+    }
+
+    fn array_edit_script<'a>(
         &mut self,
-        source: &[serde_json::Value],
-        target: &[serde_json::Value],
-    ) -> Vec<(usize, Difference)> {
-        let mut iterations = 0;
-        let res: Vec<_> = source
-            .iter()
-            .zip(target.iter())
-            .enumerate()
-            .filter_map(|(i, (s, t))| {
-                iterations += 1;
-                let elem_path = PathElement::ArrayIndex(ArrayIndex::Index(i));
-                if i > 0 { self.curr_path.pop(); }
-                self.curr_path.push(elem_path);
-                self.values(s.clone(), t.clone()).map(|diff| (i, diff))
-            })
-            .collect();
-        if iterations != 0 {
-            self.curr_path.pop();
+        source: &'a [serde_json::Value],
+        target: &'a [serde_json::Value],
+        common: Vec<(usize, usize)>,
+    ) -> Option<ArrayDifference<'a>> {
+        let mut edits = Vec::new();
+        let (mut s, mut t) = (0, 0);
+        for (next_s, next_t) in common.into_iter().chain(once((source.len(), target.len()))) {
+            // Elements between two common ones are paired up as modifications, the rest is
+            // deleted from `source` or inserted from `target`.
+            let paired = (next_s - s).min(next_t - t);
+            for k in 0..paired {
+                if let Some(diff) = self.array_element(s + k, &source[s + k], &target[t + k]) {
+                    edits.push(ArrayEdit::Modify {
+                        source_index: s + k,
+                        target_index: t + k,
+                        value_diff: diff,
+                    });
+                }
+            }
+            edits.extend((s + paired..next_s).map(|i| ArrayEdit::Delete { index: i, value: Cow::Borrowed(&source[i]) }));
+            edits.extend((t + paired..next_t).map(|j| ArrayEdit::Insert { index: j, value: Cow::Borrowed(&target[j]) }));
+            (s, t) = (next_s + 1, next_t + 1);
+        }
+
+        edits.retain(|edit| !self.is_allowed(edit));
+        match edits.is_empty() {
+            true => None,
+            false => Some(ArrayDifference::EditScript { edits }),
+        }
+    }
+
+    fn array_matched_by_key<'a>(
+        &mut self,
+        keys: &[String],
+        source: &'a [serde_json::Value],
+        target: &'a [serde_json::Value],
+    ) -> Option<ArrayDifference<'a>> {
+        // Elements that aren't objects or miss one of the key fields are matched by position
+        // against the other side's keyless elements.
+        let key_of = |value: &serde_json::Value| -> Option<String> {
+            let object = value.as_object()?;
+            let values = keys.iter().map(|key| object.get(key)).collect::<Option<Vec<_>>>()?;
+            serde_json::to_string(&values).ok()
         };
 
-        res
+        let mut target_by_key: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut keyless_targets = Vec::new();
+        for (j, value) in target.iter().enumerate().rev() {
+            match key_of(value) {
+                Some(key) => target_by_key.entry(key).or_default().push(j),
+                None => keyless_targets.push(j),
+            }
+        }
+
+        let mut matched = vec![false; target.len()];
+        let mut edits = Vec::new();
+        let mut moved = Vec::new();
+        for (i, value) in source.iter().enumerate() {
+            let j = match key_of(value) {
+                Some(key) => target_by_key.get_mut(&key).and_then(Vec::pop),
+                None => keyless_targets.pop(),
+            };
+            let Some(j) = j else {
+                edits.push(ArrayEdit::Delete { index: i, value: Cow::Borrowed(value) });
+                continue;
+            };
+            matched[j] = true;
+            match self.array_element(i, value, &target[j]) {
+                Some(diff) => edits.push(ArrayEdit::Modify {
+                    source_index: i,
+                    target_index: j,
+                    value_diff: diff,
+                }),
+                None if i != j => moved.push((i, j)),
+                None => {}
+            }
+        }
+        edits.extend(
+            target
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| !matched[*j])
+                .map(|(j, value)| ArrayEdit::Insert { index: j, value: Cow::Borrowed(value) }),
+        );
+
+        edits.retain(|edit| !self.is_allowed(edit));
+        match edits.is_empty() {
+            true => None,
+            false => Some(ArrayDifference::Matched { edits, moved }),
+        }
     }
 
-    #[must_use]
-    fn objects(
+    fn array_unordered<'a>(
         &mut self,
-        source: serde_json::Map<String, serde_json::Value>,
-        mut target: serde_json::Map<String, serde_json::Value>,
-    ) -> Option<DumbMap<String, EntryDifference>> {
-        let mut is_first = true;
-        let mut value_differences = source
-            .into_iter()
-            .filter_map(|(key, source)| {
-                let elem_path = PathElement::Key(key.clone());
-                match is_first {
-                    true => is_first = false,
-                    false => { self.curr_path.pop(); }
+        source: &'a [serde_json::Value],
+        target: &'a [serde_json::Value],
+    ) -> Option<ArrayDifference<'a>> {
+        let mut matched = vec![false; target.len()];
+        let mut unmatched_source = Vec::new();
+        let mut moved = Vec::new();
+        for (i, value) in source.iter().enumerate() {
+            // Try the same index first, so arrays that are already in order stay cheap.
+            let candidates = (i..target.len()).chain(0..i.min(target.len()));
+            let equal = candidates
+                .filter(|&j| !matched[j])
+                .find(|&j| self.array_element(i, value, &target[j]).is_none());
+            match equal {
+                Some(j) => {
+                    matched[j] = true;
+                    if i != j {
+                        moved.push((i, j));
+                    }
                 }
-                self.curr_path.push(elem_path);
+                None => unmatched_source.push(i),
+            }
+        }
 
-                if self.ignore_path(target.contains_key(&key)) {
-                    target.remove(&key);
-                    return None;
+        // Pair the remaining containers with the element of the same type they differ from the least.
+        let mut edits = Vec::new();
+        for i in unmatched_source {
+            let value = &source[i];
+            let closest = (0..target.len())
+                .filter(|&j| !matched[j] && is_same_container(value, &target[j]))
+                .filter_map(|j| {
+                    let diff = self.array_element(i, value, &target[j])?;
+                    let weight = diff.weight();
+                    (weight < leaf_count(value)).then_some((weight, j, diff))
+                })
+                .min_by_key(|(weight, j, _)| (*weight, *j));
+
+            match closest {
+                Some((_, j, diff)) => {
+                    matched[j] = true;
+                    edits.push(ArrayEdit::Modify {
+                        source_index: i,
+                        target_index: j,
+                        value_diff: diff,
+                    });
                 }
+                None => edits.push(ArrayEdit::Delete { index: i, value: Cow::Borrowed(value) }),
+            }
+        }
+        edits.extend(
+            target
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| !matched[*j])
+                .map(|(j, value)| ArrayEdit::Insert { index: j, value: Cow::Borrowed(value) }),
+        );
+
+        edits.retain(|edit| !self.is_allowed(edit));
+        match edits.is_empty() {
+            true => None,
+            false => Some(ArrayDifference::Matched { edits, moved }),
+        }
+    }
+
+    fn array_element<'a>(
+        &mut self,
+        index: usize,
+        source: &'a serde_json::Value,
+        target: &'a serde_json::Value,
+    ) -> Option<Difference<'a>> {
+        self.push(PathElement::ArrayIndex(ArrayIndex::Index(index)));
+        let diff = match self.ignore_path(true) {
+            true => None,
+            false => self.values(source, target),
+        };
+        self.pop();
+        diff
+    }
 
-                let Some(target) = target.remove(&key) else {
-                    return Some((key, EntryDifference::Extra {
-                        value: source
-                    }));
-                };
+    fn compare_array_elements<'a>(
+        &mut self,
+        source: &'a [serde_json::Value],
+        target: &'a [serde_json::Value],
+    ) -> Vec<(usize, Difference<'a>)> {
+        source
+            .iter()
+            .zip(target.iter())
+            .enumerate()
+            .filter_map(|(i, (s, t))| self.array_element(i, s, t).map(|diff| (i, diff)))
+            .collect()
+    }
 
-                self.values(source, target).map(|diff| (key, EntryDifference::Value { value_diff: diff }))
+    #[must_use]
+    fn objects<'a>(
+        &mut self,
+        source: &'a serde_json::Map<String, serde_json::Value>,
+        target: &'a serde_json::Map<String, serde_json::Value>,
+    ) -> Option<DumbMap<Cow<'a, str>, EntryDifference<'a>>> {
+        let mut value_differences = source
+            .iter()
+            .filter_map(|(key, source)| {
+                self.push(PathElement::Key(key.clone()));
+                let diff = self.entry(source, target.get(key));
+                self.pop();
+                diff.map(|diff| (Cow::Borrowed(key.as_str()), diff))
             })
             .collect::<Vec<_>>();
 
-        if !is_first { self.curr_path.pop(); }
-
-        value_differences.extend(target.into_iter().map(|(missing_key, missing_value)| {
+        let missing = target.iter().filter(|(key, _)| !source.contains_key(*key) && self.compare_mode != CompareMode::ContainedIn);
+        value_differences.extend(missing.map(|(missing_key, missing_value)| {
             (
-                missing_key,
+                Cow::Borrowed(missing_key.as_str()),
                 EntryDifference::Missing {
-                    value: missing_value,
+                    value: Cow::Borrowed(missing_value),
                 },
             )
         }));
@@ -244,13 +1065,42 @@ impl Diff {
         }
     }
 
-    pub fn compare(mut self) -> Option<Difference> {
-        self.values(self.source.clone(), self.target.clone())
+    /// Compares the entry of `source` at the current path with the one of `target`, if it has any.
+    fn entry<'a>(&mut self, source: &'a serde_json::Value, target: Option<&'a serde_json::Value>) -> Option<EntryDifference<'a>> {
+        if self.ignore_path(target.is_some()) {
+            return None;
+        }
+
+        let Some(target) = target else {
+            if self.compare_mode == CompareMode::Contains {
+                return None;
+            }
+            return Some(EntryDifference::Extra {
+                value: Cow::Borrowed(source)
+            });
+        };
+
+        self.values(source, target).map(|diff| EntryDifference::Value { value_diff: diff })
     }
 
-    fn values(&mut self, source: serde_json::Value, target: serde_json::Value) -> Option<Difference> {
+    fn values<'a>(&mut self, source: &'a serde_json::Value, target: &'a serde_json::Value) -> Option<Difference<'a>> {
         use serde_json::Value::{Array, Bool, Null, Number, Object, String};
 
+        match self.comparator_outcome(source, target) {
+            Some((Outcome::Equal, _)) => return None,
+            Some((Outcome::Different, rule)) => {
+                return Some(Difference::Custom {
+                    source_value: Cow::Borrowed(source),
+                    target_value: Cow::Borrowed(target),
+                    comparator: rule.path.clone(),
+                })
+            }
+            _ => {}
+        }
+        if let Some(diff) = self.compare_date_times(source, target) {
+            return diff;
+        }
+
         match (source, target) {
             (Null, Null) => None,
             (Bool(source), Bool(target)) => {
@@ -258,8 +1108,8 @@ impl Diff {
                     None
                 } else {
                     Some(Difference::Scalar(ScalarDifference::Bool {
-                        source,
-                        target,
+                        source: *source,
+                        target: *target,
                     }))
                 }
             }
@@ -278,88 +1128,324 @@ impl Diff {
             (Null, Array(target)) if self.equate_empty_arrays && target.len().eq(&0) => None,
             (source, target) => {
                 Some(Difference::Type {
-                    source_type: source.clone().into(),
-                    source_value: source,
-                    target_type: target.clone().into(),
-                    target_value: target,
+                    source_type: source.into(),
+                    source_value: Cow::Borrowed(source),
+                    target_type: target.into(),
+                    target_value: Cow::Borrowed(target),
                 })
             }
         }
     }
 
+    /// Compares `source` and `target` as datetimes if the current path has datetime options and
+    /// both of them parse, or returns `None` to compare them as usual.
+    fn compare_date_times<'a>(
+        &self,
+        source: &'a serde_json::Value,
+        target: &'a serde_json::Value,
+    ) -> Option<Option<Difference<'a>>> {
+        let options = self.date_times.best_match().copied().or(self.default_date_time.as_ref())?;
+        let delta = options.delta(source, target)?;
+        Some((!options.is_within(delta)).then_some(Difference::Scalar(ScalarDifference::DateTime {
+            source: Cow::Borrowed(source),
+            target: Cow::Borrowed(target),
+            delta,
+            tolerance: options.tolerance,
+            date_only: options.date_only,
+        })))
+    }
 
-    fn compare_strings(&self, source:String, target: String) -> Option<Difference> {
-        if !self.approx_date_time_eq_duration.is_zero() {
-            let source_datetime = DateTime::parse_from_rfc3339(source.as_str());
-            let target_datetime = DateTime::parse_from_rfc3339(target.as_str());
-
-            match (source_datetime, target_datetime) {
-                (Ok(source_date_time), Ok(target_date_time)) => {
-                    let delta = source_date_time - target_date_time;
-                    let delta = delta.abs().to_std().unwrap();
-                    if delta.gt(&self.approx_date_time_eq_duration) {
-                        return Some(Difference::Scalar(ScalarDifference::String {
-                            source,
-                            target,
-                        }))
-                    } else {
-                        return None
-                    }
-                },
-                (_, _) => {},
-            }
-        }
+    fn compare_strings<'a>(&self, source: &'a str, target: &'a str) -> Option<Difference<'a>> {
         if source == target {
             None
         } else {
             Some(Difference::Scalar(ScalarDifference::String {
-                source,
-                target,
+                source: Cow::Borrowed(source),
+                target: Cow::Borrowed(target),
+                text_diff: None,
             }))
         }
     }
 
-    fn compare_numbers(&self, source: Number, target: Number) -> Option<Difference> {
-        if source.is_u64() && target.is_u64() || source.is_i64() && target.is_i64() {
-            if source == target {
-                None
-            } else {
-                Some(Difference::Scalar(ScalarDifference::Number {
-                    source,
-                    target,
-                }))
-            }
-        } else if source.is_f64() || target.is_f64() {
-            if relative_eq!(source.as_f64().unwrap(), target.as_f64().unwrap(), epsilon = self.approx_float_eq_epsilon) {
-                None
-            } else {
-                Some(Difference::Scalar(ScalarDifference::Number {
-                    source,
-                    target,
-                }))
-            }
-        } else {
-            None
+    fn compare_numbers(&self, source: &Number, target: &Number) -> Option<Difference<'static>> {
+        let plain = || Some(Difference::Scalar(ScalarDifference::Number { source: source.clone(), target: target.clone() }));
+        if !self.equate_integers_and_floats && number::is_float(source) != number::is_float(target) {
+            return plain();
         }
+        if number::exact_eq(source, target) {
+            return None;
+        }
+        let has_float = number::is_float(source) || number::is_float(target);
+        let (Some((tolerance, tolerance_path)), Some(source_f64), Some(target_f64)) =
+            (self.number_tolerance(has_float), source.as_f64(), target.as_f64())
+        else {
+            return plain();
+        };
+        if tolerance.eq(source_f64, target_f64) {
+            return None;
+        }
+
+        let delta = target_f64 - source_f64;
+        Some(Difference::Scalar(ScalarDifference::ApproxNumber {
+            source: source.clone(),
+            target: target.clone(),
+            delta,
+            relative_error: delta.abs() / source_f64.abs().max(target_f64.abs()),
+            tolerance,
+            tolerance_path: tolerance_path.map(str::to_string),
+        }))
+    }
+
+    /// Consults the comparators for the current path in the order they were registered, and
+    /// returns the decision of the first one that decides, along with that comparator.
+    fn comparator_outcome(&self, source: &serde_json::Value, target: &serde_json::Value) -> Option<(Outcome, &'d ComparatorRule)> {
+        if self.diff.comparators.is_empty() {
+            return None;
+        }
+        let mut matching: Vec<usize> = self.comparators.matches().copied().collect();
+        matching.extend(self.diff.comparators.iter().enumerate().filter_map(|(i, rule)| match &rule.when {
+            When::Predicate(predicate) if predicate(&self.curr_path) => Some(i),
+            _ => None,
+        }));
+        matching.sort_unstable();
+        matching.dedup();
+
+        matching
+            .into_iter()
+            .map(|i| &self.diff.comparators[i])
+            .map(|rule| (rule.comparator.compare(&self.curr_path, source, target), rule))
+            .find(|(outcome, _)| *outcome != Outcome::Recurse)
     }
 
+    /// The tolerance for the numbers at the current path, if any, and the path it was set for,
+    /// or `None` if it's [`DiffBuilder::approx_float_eq_epsilon`], which only applies if `has_float`.
+    fn number_tolerance(&self, has_float: bool) -> Option<(Tolerance, Option<&str>)> {
+        match self.number_tolerances.best_match() {
+            Some((tolerance, path)) => Some((*tolerance, Some(path))),
+            None if has_float && self.approx_float_eq_epsilon > 0.0 => {
+                Some((Tolerance::Absolute(self.approx_float_eq_epsilon), None))
+            }
+            None => None,
+        }
+    }
+
+    /// Returns the key fields configured for the array at the current path.
+    fn array_key(&self) -> Option<&'d [String]> {
+        self.array_keys.best_match().copied()
+    }
+
+    /// Whether `compare_mode` allows leaving out `edit`.
+    fn is_allowed(&self, edit: &ArrayEdit) -> bool {
+        match edit {
+            ArrayEdit::Delete { .. } => self.compare_mode == CompareMode::Contains,
+            ArrayEdit::Insert { .. } => self.compare_mode == CompareMode::ContainedIn,
+            ArrayEdit::Modify { .. } => false,
+        }
+    }
+
+    fn array_mode(&self) -> ArrayDiffMode {
+        self.array_modes.best_match().map_or(self.array_diff_mode, |mode| *mode)
+    }
+
+    /// Whether the entry at the current path is ignored. Entries that `target` doesn't have are
+    /// only ignored by rules that allow missing values.
     fn ignore_path(&self, target_has_key: bool) -> bool {
-        let path = self.ignore_paths.iter().find(|p| p.0.eq(&self.curr_path));
+        self.ignore_paths.matches().any(|ignore_missing| target_has_key || *ignore_missing)
+    }
+}
 
-        match path {
-            Some(IgnorePath(path, _))
-            if path.eq(&self.curr_path) && target_has_key => true,
-            Some(IgnorePath(path, ignore_missing))
-            if path.eq(&self.curr_path) && !target_has_key && *ignore_missing => true,
-            Some(IgnorePath(path, ignore_missing))
-            if path.eq(&self.curr_path) && !target_has_key && !ignore_missing => false,
-            _ => false,
+/// Hashes `value` consistently with its `==`, which ignores the order of object entries.
+fn hash_value(value: &serde_json::Value, state: &mut impl Hasher) {
+    std::mem::discriminant(value).hash(state);
+    match value {
+        serde_json::Value::Null => {}
+        serde_json::Value::Bool(b) => b.hash(state),
+        serde_json::Value::Number(n) => n.to_string().hash(state),
+        serde_json::Value::String(s) => s.hash(state),
+        serde_json::Value::Array(values) => {
+            values.len().hash(state);
+            values.iter().for_each(|value| hash_value(value, state));
+        }
+        serde_json::Value::Object(entries) => {
+            let entries = entries.iter().fold(0u64, |sum, (key, value)| {
+                let mut hasher = DefaultHasher::new();
+                key.hash(&mut hasher);
+                hash_value(value, &mut hasher);
+                sum.wrapping_add(hasher.finish())
+            });
+            entries.hash(state);
+        }
+    }
+}
+
+impl Difference<'_> {
+    /// The number of leaf values that differ.
+    fn weight(&self) -> usize {
+        match self {
+            Difference::Scalar(_) | Difference::Type { .. } | Difference::Custom { .. } => 1,
+            Difference::Object { different_entries } => different_entries
+                .0
+                .iter()
+                .map(|(_, entry)| match entry {
+                    EntryDifference::Missing { value } | EntryDifference::Extra { value } => leaf_count(value),
+                    EntryDifference::Value { value_diff } => value_diff.weight(),
+                })
+                .sum(),
+            Difference::Array(diff) => diff.weight(),
+        }
+    }
+}
+
+impl ArrayDifference<'_> {
+    fn weight(&self) -> usize {
+        let pairs_weight = |pairs: &DumbMap<usize, Difference>| pairs.0.iter().map(|(_, diff)| diff.weight()).sum::<usize>();
+        match self {
+            ArrayDifference::PairsOnly { different_pairs } => pairs_weight(different_pairs),
+            ArrayDifference::Shorter { different_pairs, missing_elements, .. } => {
+                different_pairs.as_ref().map_or(0, pairs_weight) + missing_elements.iter().map(|value| leaf_count(value)).sum::<usize>()
+            }
+            ArrayDifference::Longer { different_pairs, extra_length, .. } => {
+                different_pairs.as_ref().map_or(0, pairs_weight) + extra_length
+            }
+            ArrayDifference::EditScript { edits } | ArrayDifference::Matched { edits, .. } => edits
+                .iter()
+                .map(|edit| match edit {
+                    ArrayEdit::Insert { value, .. } | ArrayEdit::Delete { value, .. } => leaf_count(value),
+                    ArrayEdit::Modify { value_diff, .. } => value_diff.weight(),
+                })
+                .sum(),
         }
     }
 }
 
+impl Difference<'_> {
+    /// Converts this difference into one that doesn't borrow from the compared documents.
+    pub fn into_owned(self) -> Difference<'static> {
+        match self {
+            Difference::Scalar(diff) => Difference::Scalar(diff.into_owned()),
+            Difference::Type { source_type, source_value, target_type, target_value } => Difference::Type {
+                source_type,
+                source_value: Cow::Owned(source_value.into_owned()),
+                target_type,
+                target_value: Cow::Owned(target_value.into_owned()),
+            },
+            Difference::Custom { source_value, target_value, comparator } => Difference::Custom {
+                source_value: Cow::Owned(source_value.into_owned()),
+                target_value: Cow::Owned(target_value.into_owned()),
+                comparator,
+            },
+            Difference::Array(diff) => Difference::Array(diff.into_owned()),
+            Difference::Object { different_entries } => Difference::Object {
+                different_entries: DumbMap(
+                    different_entries
+                        .0
+                        .into_iter()
+                        .map(|(key, entry)| (Cow::Owned(key.into_owned()), entry.into_owned()))
+                        .collect(),
+                ),
+            },
+        }
+    }
+}
+
+impl EntryDifference<'_> {
+    pub fn into_owned(self) -> EntryDifference<'static> {
+        match self {
+            EntryDifference::Missing { value } => EntryDifference::Missing { value: Cow::Owned(value.into_owned()) },
+            EntryDifference::Extra { value } => EntryDifference::Extra { value: Cow::Owned(value.into_owned()) },
+            EntryDifference::Value { value_diff } => EntryDifference::Value { value_diff: value_diff.into_owned() },
+        }
+    }
+}
+
+impl ArrayDifference<'_> {
+    pub fn into_owned(self) -> ArrayDifference<'static> {
+        let owned_pairs = |pairs: DumbMap<usize, Difference>| {
+            DumbMap(pairs.0.into_iter().map(|(index, diff)| (index, diff.into_owned())).collect())
+        };
+        let owned_edits = |edits: Vec<ArrayEdit>| edits.into_iter().map(ArrayEdit::into_owned).collect();
+        match self {
+            ArrayDifference::PairsOnly { different_pairs } => ArrayDifference::PairsOnly {
+                different_pairs: owned_pairs(different_pairs),
+            },
+            ArrayDifference::Shorter { different_pairs, missing_elements, source_length } => ArrayDifference::Shorter {
+                different_pairs: different_pairs.map(owned_pairs),
+                missing_elements: missing_elements.into_iter().map(|value| Cow::Owned(value.into_owned())).collect(),
+                source_length,
+            },
+            ArrayDifference::Longer { different_pairs, extra_length, target_length } => ArrayDifference::Longer {
+                different_pairs: different_pairs.map(owned_pairs),
+                extra_length,
+                target_length,
+            },
+            ArrayDifference::EditScript { edits } => ArrayDifference::EditScript { edits: owned_edits(edits) },
+            ArrayDifference::Matched { edits, moved } => ArrayDifference::Matched { edits: owned_edits(edits), moved },
+        }
+    }
+}
+
+impl ArrayEdit<'_> {
+    pub fn into_owned(self) -> ArrayEdit<'static> {
+        match self {
+            ArrayEdit::Insert { index, value } => ArrayEdit::Insert { index, value: Cow::Owned(value.into_owned()) },
+            ArrayEdit::Delete { index, value } => ArrayEdit::Delete { index, value: Cow::Owned(value.into_owned()) },
+            ArrayEdit::Modify { source_index, target_index, value_diff } => ArrayEdit::Modify {
+                source_index,
+                target_index,
+                value_diff: value_diff.into_owned(),
+            },
+        }
+    }
+}
+
+impl ScalarDifference<'_> {
+    pub fn into_owned(self) -> ScalarDifference<'static> {
+        match self {
+            ScalarDifference::Bool { source, target } => ScalarDifference::Bool { source, target },
+            ScalarDifference::String { source, target, text_diff } => ScalarDifference::String {
+                source: Cow::Owned(source.into_owned()),
+                target: Cow::Owned(target.into_owned()),
+                text_diff: text_diff.map(|edits| edits.into_iter().map(TextEdit::into_owned).collect()),
+            },
+            ScalarDifference::Number { source, target } => ScalarDifference::Number { source, target },
+            ScalarDifference::ApproxNumber { source, target, delta, relative_error, tolerance, tolerance_path } => {
+                ScalarDifference::ApproxNumber { source, target, delta, relative_error, tolerance, tolerance_path }
+            }
+            ScalarDifference::DateTime { source, target, delta, tolerance, date_only } => ScalarDifference::DateTime {
+                source: Cow::Owned(source.into_owned()),
+                target: Cow::Owned(target.into_owned()),
+                delta,
+                tolerance,
+                date_only,
+            },
+        }
+    }
+}
+
+/// The number of scalar values in `value`.
+fn leaf_count(value: &serde_json::Value) -> usize {
+    match value {
+        serde_json::Value::Array(values) => values.iter().map(leaf_count).sum::<usize>().max(1),
+        serde_json::Value::Object(map) => map.values().map(leaf_count).sum::<usize>().max(1),
+        _ => 1,
+    }
+}
+
+fn is_same_container(source: &serde_json::Value, target: &serde_json::Value) -> bool {
+    matches!(
+        (source, target),
+        (serde_json::Value::Array(_), serde_json::Value::Array(_)) | (serde_json::Value::Object(_), serde_json::Value::Object(_))
+    )
+}
+
 impl From<serde_json::Value> for Type {
     fn from(value: serde_json::Value) -> Self {
+        Type::from(&value)
+    }
+}
+
+impl From<&serde_json::Value> for Type {
+    fn from(value: &serde_json::Value) -> Self {
         match value {
             serde_json::Value::Null => Type::Null,
             serde_json::Value::Bool(_) => Type::Bool,
@@ -392,13 +1478,45 @@ pub enum ArrayIndex {
 pub enum PathElement {
     Key(String),
     ArrayIndex(ArrayIndex),
+    /// `*`, any single key
+    AnyKey,
+    /// `**`, any number of keys and indices, including none
+    AnyDepth,
 }
 
 #[derive(PartialEq, Clone, Debug)]
 pub struct IgnorePath(pub Path, pub bool);
 
-#[derive(PartialEq, Clone, Debug, Default)]
-pub struct Path(Vec<PathElement>);
+#[derive(PartialEq, Clone, Debug)]
+pub struct ArrayKey(pub Path, pub Vec<String>);
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct ArrayMode(pub Path, pub ArrayDiffMode);
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct NumberTolerance(pub Path, pub Tolerance);
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct DateTimeRule(pub Path, pub DateTimeOptions);
+
+/// The path of a value, or a pattern matching the paths of values.
+///
+/// The second field is whether the path was parsed from a JSON Pointer, whose tokens like `0`
+/// match both array indices and object keys.
+#[derive(Clone, Default)]
+pub struct Path(Vec<PathElement>, bool);
+
+impl PartialEq for Path {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Debug for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Path").field(&self.0).finish()
+    }
+}
 
 impl Deref for Path {
     type Target = Vec<PathElement>;
@@ -416,32 +1534,124 @@ impl DerefMut for Path {
 
 impl From<Vec<PathElement>> for Path {
     fn from(value: Vec<PathElement>) -> Self {
-        Self(value)
+        Self(value, false)
+    }
+}
+
+impl Path {
+    /// Whether both paths consist of exactly the same elements, treating `[_]` only as equal to itself.
+    fn is_same(&self, other: &Path) -> bool {
+        Pattern::from(self).is_same(other.into())
+    }
+}
+
+impl Path {
+    /// Parses an [RFC 6901](https://www.rfc-editor.org/rfc/rfc6901) JSON Pointer, e.g. `/items/0/name`.
+    ///
+    /// A pointer doesn't tell array indices from object keys, so tokens like `0` become
+    /// [`ArrayIndex::Index`], which in rules also match object members with that name. Indices in
+    /// dotted paths, like `[0]`, only match array elements.
+    pub fn from_json_pointer(pointer: &str) -> Result<Path, PathError> {
+        if pointer.is_empty() {
+            return Ok(Path::default());
+        }
+        let Some(tokens) = pointer.strip_prefix('/') else {
+            return Err(PathError::new(0, "JSON Pointer must start with `/`"));
+        };
+
+        let mut position = 1;
+        let mut elements = Vec::new();
+        for token in tokens.split('/') {
+            let element = match index_token(token) {
+                Some(index) => PathElement::ArrayIndex(ArrayIndex::Index(index)),
+                None => PathElement::Key(
+                    unescape_pointer_token(token).ok_or_else(|| PathError::new(position, format!("Invalid escape in `{token}`")))?,
+                ),
+            };
+            elements.push(element);
+            position += token.len() + 1;
+        }
+        Ok(Path(elements, true))
+    }
+
+    /// Formats the path as an [RFC 6901](https://www.rfc-editor.org/rfc/rfc6901) JSON Pointer,
+    /// or returns `None` if it contains wildcards.
+    pub fn to_json_pointer(&self) -> Option<String> {
+        let mut pointer = String::new();
+        for element in self.iter() {
+            pointer.push('/');
+            match element {
+                PathElement::Key(key) => pointer.push_str(&escape_pointer_token(key)),
+                PathElement::ArrayIndex(ArrayIndex::Index(index)) => pointer.push_str(&index.to_string()),
+                PathElement::ArrayIndex(ArrayIndex::All) | PathElement::AnyKey | PathElement::AnyDepth => return None,
+            }
+        }
+        Some(pointer)
+    }
+}
+
+/// Formats the path in the dotted syntax accepted by [`DiffBuilder::ignore_path`].
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, element) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            match element {
+                PathElement::Key(key) if needs_quotes(key, i == 0) => write!(f, "'{}'", key.replace('\'', "''"))?,
+                PathElement::Key(key) => f.write_str(key)?,
+                PathElement::ArrayIndex(ArrayIndex::Index(index)) => write!(f, "[{index}]")?,
+                PathElement::ArrayIndex(ArrayIndex::All) => f.write_str("[_]")?,
+                PathElement::AnyKey => f.write_str("*")?,
+                PathElement::AnyDepth => f.write_str("**")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `key` has to be quoted in a dotted path, so it isn't read as a wildcard, as more than
+/// one element or, at the start, as a JSON Pointer or JSONPath expression.
+fn needs_quotes(key: &str, first: bool) -> bool {
+    key.is_empty()
+        || key.contains(['.', '[', ']', '\''])
+        || key == "*"
+        || key == "**"
+        || first && (key.starts_with('/') || key == "$")
+}
+
+/// The array index a JSON Pointer token refers to, if it is one.
+pub(crate) fn index_token(token: &str) -> Option<usize> {
+    let canonical = token == "0" || !token.starts_with('0');
+    match canonical && !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit()) {
+        true => token.parse().ok(),
+        false => None,
     }
 }
 
 impl FromStr for Path {
-    type Err = String;
+    type Err = PathError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Path(parse_element_path(s)?))
+        Ok(Path(parse_element_path(s)?, false))
     }
 }
 
 impl TryFrom<&str> for Path {
-    type Error = String;
+    type Error = PathError;
 
     fn try_from(s: &str) -> Result<Self, Self::Error> {
         s.parse()
     }
 }
 
-
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::time::Duration;
+    use serde::Serialize;
     use serde_json::json;
-    use crate::DiffBuilder;
+    use crate::{diff_serializable, parse_path, ArrayDiffMode, ArrayIndex, CompareMode, DateTimeFormat, DateTimeOptions, DiffBuilder, Error, Path, PathElement, PathError, Tolerance, Type};
 
     #[test]
     fn equal_objects() {
@@ -541,6 +1751,81 @@ mod tests {
         assert_eq!(true, diff.is_none(), "diff should be None, but got: {:?}", diff);
     }
 
+    #[test]
+    fn ignore_wildcards() {
+        let obj1 = json!({
+            "updated_at": 1,
+            "users": {
+                "john": {"metadata": {"a": 1}, "name": "John", "posts": [{"id": 1, "updated_at": 1}]},
+                "jane": {"metadata": {"b": 1}, "name": "Jane"}
+            },
+            "items": [{"id": 1, "seen": 1}, {"id": 2, "seen": 2}]
+        });
+
+        let obj2 = json!({
+            "updated_at": 2,
+            "users": {
+                "john": {"metadata": {"a": 2}, "name": "John", "posts": [{"id": 1, "updated_at": 2}]},
+                "jane": {"metadata": null, "name": "Jane"}
+            },
+            "items": [{"id": 1, "seen": 3}, {"id": 2, "seen": 4}]
+        });
+
+        let diff = DiffBuilder::default()
+            .ignore_path("**.updated_at")
+            .ignore_path("users.*.metadata")
+            .ignore_path("items.[*].seen")
+            .source(obj1.clone()).target(obj2.clone()).build().unwrap();
+
+        let diff = diff.compare();
+
+        assert!(diff.is_none(), "diff should be None, but got: {:?}", diff);
+
+        let diff = DiffBuilder::default()
+            .ignore_path("*.updated_at")
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = serde_json::to_value(diff.compare()).unwrap();
+
+        assert!(diff["different_entries"].get("updated_at").is_some(), "`*` should match exactly one key, but got: {}", diff);
+    }
+
+    #[test]
+    fn dollar_keys() {
+        let obj1 = json!({"$schema": "a", "$defs": {"items": [{"id": 1, "v": 1}, {"id": 2}]}, "v": 1});
+        let obj2 = json!({"$schema": "b", "$defs": {"items": [{"id": 2}, {"id": 1, "v": 2}]}, "v": 1});
+
+        let diff = DiffBuilder::default()
+            .ignore_path("$schema")
+            .array_key("$defs.items", "id")
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), "$defs.items.[0].v: - 1 / + 2\n");
+    }
+
+    #[test]
+    fn ignore_array_elements() {
+        let obj1 = json!({"tags": ["a", "b"], "items": [{"id": 1}]});
+        let obj2 = json!({"tags": ["x", "y"], "items": [{"id": 2}]});
+
+        let diff = DiffBuilder::default()
+            .ignore_path("tags.[0]")
+            .ignore_path("items.[_]")
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), "tags.[1]: - \"b\" / + \"y\"\n");
+
+        let diff = DiffBuilder::default()
+            .ignore_path("/tags/1")
+            .source(json!({"tags": ["a", "b"]})).target(json!({"tags": ["x", "y"]})).build().unwrap();
+
+        assert_eq!(diff.compare().unwrap().to_string(), "tags.[0]: - \"a\" / + \"x\"\n");
+    }
+
     #[test]
     fn approx_float_eq() {
         let obj1 = json!({
@@ -560,6 +1845,117 @@ mod tests {
         assert_eq!(true, diff.is_none(), "diff should be None, but got: {:?}", diff);
     }
 
+    #[test]
+    fn approx_float_eq_skips_integers() {
+        let diff = DiffBuilder::default()
+            .approx_float_eq_epsilon(5.0)
+            .number_tolerance_at("c", Tolerance::Absolute(5.0))
+            .source(json!({"a": 1, "b": 1, "c": 1})).target(json!({"a": 3, "b": 3.0, "c": 3})).build().unwrap();
+
+        assert_eq!(diff.compare().unwrap().to_string(), "a: - 1 / + 3\n");
+    }
+
+    #[test]
+    fn number_tolerances() {
+        let obj1 = json!({
+            "prices": [{"amount": 10.004}, {"amount": 10}],
+            "metrics": {"latency": 100, "errors": 1.0},
+            "count": 1,
+            "ratio": 0.3,
+        });
+
+        let obj2 = json!({
+            "prices": [{"amount": 10}, {"amount": 10.02}],
+            "metrics": {"latency": 100.9, "errors": 1.02},
+            "count": 1.0000001,
+            "ratio": 0.30000000000000004,
+        });
+
+        let diff = DiffBuilder::default()
+            .number_tolerance_at("prices.[_].amount", Tolerance::Absolute(0.01))
+            .number_tolerance_at("metrics.*", Tolerance::Relative(0.01))
+            .number_tolerance_at("count", Tolerance::Absolute(0.001))
+            .number_tolerance_at("ratio", Tolerance::Ulps(1))
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), [
+            "metrics.errors: - 1.0 / + 1.02 (off by 0.02 or 1.96%, tolerance 1% for `metrics.*`)",
+            "prices.[1].amount: - 10 / + 10.02 (off by 0.02 or 0.2%, tolerance 0.01 for `prices.[_].amount`)",
+            "",
+        ].join("\n"));
+
+        let diff = DiffBuilder::default()
+            .approx_float_eq_epsilon(1.0)
+            .number_tolerance_at("b", Tolerance::Absolute(0.0))
+            .source(json!({"a": 1, "b": 1})).target(json!({"a": 2.0, "b": 1.5})).build().unwrap();
+
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), "b: - 1 / + 1.5 (off by 0.5 or 33.3%, tolerance 0 for `b`)\n");
+
+        // An absolute tolerance doesn't also allow a relative error of one `f64::EPSILON`.
+        let diff = DiffBuilder::default()
+            .number_tolerance_at("a", Tolerance::Absolute(0.5))
+            .source(json!({"a": 1e16})).target(json!({"a": 1.0000000000000002e16})).build().unwrap();
+
+        assert!(diff.compare().is_some());
+    }
+
+    #[test]
+    fn most_specific_number_tolerance() {
+        let obj1 = json!({"prices": [{"amount": 10}, {"amount": 10}], "metrics": {"amount": 10}});
+        let obj2 = json!({"prices": [{"amount": 10.5}, {"amount": 10.5}], "metrics": {"amount": 10.5}});
+
+        let diff = DiffBuilder::default()
+            .number_tolerance_at("/prices/0/amount", Tolerance::Absolute(0.0))
+            .number_tolerance_at("prices.[_].amount", Tolerance::Absolute(1.0))
+            .number_tolerance_at("metrics.*", Tolerance::Absolute(0.0))
+            .number_tolerance_at("**.amount", Tolerance::Absolute(1.0))
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), [
+            "metrics.amount: - 10 / + 10.5 (off by 0.5 or 4.76%, tolerance 0 for `metrics.*`)",
+            "prices.[0].amount: - 10 / + 10.5 (off by 0.5 or 4.76%, tolerance 0 for `prices.[0].amount`)",
+            "",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn value_types() {
+        assert!(matches!(Type::from(json!([1])), Type::Array));
+        assert!(matches!(Type::from(&json!({"a": 1})), Type::Object));
+    }
+
+    #[test]
+    fn mixed_numbers() {
+        let obj1 = json!({"signed": -1, "int": 1, "exponent": 1500});
+        let obj2 = json!({"signed": u64::MAX, "int": 1.0, "exponent": 1.5e3});
+
+        let diff = DiffBuilder::default().source(obj1.clone()).target(obj2.clone()).build().unwrap();
+
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), "signed: - -1 / + 18446744073709551615\n");
+
+        let diff = DiffBuilder::default()
+            .equate_integers_and_floats(false)
+            .approx_float_eq_epsilon(0.5)
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), [
+            "exponent: - 1500 / + 1500.0",
+            "int: - 1 / + 1.0",
+            "signed: - -1 / + 18446744073709551615",
+            "",
+        ].join("\n"));
+    }
+
     #[test]
     fn approx_date_time_eq() {
         let obj1 = json!({
@@ -578,4 +1974,505 @@ mod tests {
 
         assert_eq!(true, diff.is_none(), "diff should be None, but got: {:?}", diff);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn date_time_rules() {
+        let obj1 = json!({
+            "created": "2023-07-25T15:30:00+02:00",
+            "events": [{"at": 1690291800}, {"at": 1690291800}],
+            "day": "2023-07-25 23:59:59",
+            "updated": "2023-07-25T13:30:00Z",
+        });
+
+        let obj2 = json!({
+            "created": "Tue, 25 Jul 2023 13:30:01 +0000",
+            "events": [{"at": "2023-07-25 13:30:00.5"}, {"at": "2023-07-25 13:30:03"}],
+            "day": "2023-07-25T00:00:00Z",
+            "updated": "2023-07-25T13:30:05Z",
+        });
+
+        let formats = vec![DateTimeFormat::Rfc3339, DateTimeFormat::Rfc2822, DateTimeFormat::Naive, DateTimeFormat::EpochSeconds];
+        let options = DateTimeOptions { formats, tolerance: Duration::from_secs(1), date_only: false };
+        let diff = DiffBuilder::default()
+            .date_time_at("created", options.clone())
+            .date_time_at("events.[_].at", options)
+            .date_time_at("day", DateTimeOptions {
+                formats: vec![DateTimeFormat::Naive, DateTimeFormat::Rfc3339],
+                date_only: true,
+                ..Default::default()
+            })
+            .approx_date_time_eq_duration(Duration::from_secs(10))
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), "events.[1].at: - 1690291800 / + \"2023-07-25 13:30:03\" (off by 3s, tolerance 1s)\n");
+        assert_eq!(
+            serde_json::to_value(&diff).unwrap()["different_entries"]["events"]["value_diff"]["different_pairs"]["1"]["different_entries"]["at"],
+            json!({
+                "entry_difference": "value",
+                "value_diff": {"difference_of": "scalar", "source": 1690291800, "target": "2023-07-25 13:30:03", "delta": 3.0, "tolerance": 1.0, "date_only": false},
+            }),
+        );
+    }
+
+    #[test]
+    fn lcs_array_diff() {
+        let obj1 = json!({
+            "items": [1, 2, 3, 4, 5],
+        });
+
+        let obj2 = json!({
+            "items": [0, 1, 2, 4, 6],
+        });
+
+        let diff = DiffBuilder::default()
+            .array_diff_mode(ArrayDiffMode::Lcs)
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = serde_json::to_value(diff.compare()).unwrap();
+
+        assert_eq!(diff["different_entries"]["items"]["value_diff"]["edits"], json!([
+            {"edit": "insert", "index": 0, "value": 0},
+            {"edit": "delete", "index": 2, "value": 3},
+            {
+                "edit": "modify",
+                "source_index": 4,
+                "target_index": 4,
+                "value_diff": {"difference_of": "scalar", "source": 5, "target": 6},
+            },
+        ]));
+
+        // Elements only equal through the options are paired up between the exactly equal ones.
+        let diff = DiffBuilder::default()
+            .array_diff_mode(ArrayDiffMode::Lcs)
+            .ignore_path("[_].at")
+            .source(json!([{"id": 1, "at": 1}, 0, {"id": 2, "at": 1}]))
+            .target(json!([{"id": 1, "at": 2}, 0, {"id": 2, "at": 2}]))
+            .build().unwrap();
+
+        assert!(diff.compare().is_none());
+
+        // Arrays that are too long to align are compared by index.
+        let diff = DiffBuilder::default()
+            .array_diff_mode(ArrayDiffMode::Lcs)
+            .source(json!((0..3000).collect::<Vec<_>>()))
+            .target(json!((1..3001).collect::<Vec<_>>()))
+            .build().unwrap();
+
+        let diff = serde_json::to_value(diff.compare()).unwrap();
+
+        assert_eq!(diff["array_difference"], "pairs_only");
+        assert_eq!(diff["different_pairs"].as_object().unwrap().len(), 3000);
+    }
+
+    #[test]
+    fn array_key() {
+        let obj1 = json!({
+            "items": [
+                {"id": 1, "name": "foo"},
+                {"id": 2, "name": "bar"},
+                {"id": 3, "name": "baz"},
+            ],
+        });
+
+        let obj2 = json!({
+            "items": [
+                {"id": 4, "name": "qux"},
+                {"id": 2, "name": "bar"},
+                {"id": 1, "name": "FOO"},
+            ],
+        });
+
+        let diff = DiffBuilder::default()
+            .array_key("items.[_]", "id")
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = serde_json::to_value(diff.compare()).unwrap();
+
+        assert_eq!(diff["different_entries"]["items"]["value_diff"], json!({
+            "difference_of": "array",
+            "array_difference": "matched",
+            "edits": [
+                {
+                    "edit": "modify",
+                    "source_index": 0,
+                    "target_index": 2,
+                    "value_diff": {
+                        "difference_of": "object",
+                        "different_entries": {
+                            "name": {
+                                "entry_difference": "value",
+                                "value_diff": {"difference_of": "scalar", "source": "foo", "target": "FOO"},
+                            },
+                        },
+                    },
+                },
+                {"edit": "delete", "index": 2, "value": {"id": 3, "name": "baz"}},
+                {"edit": "insert", "index": 0, "value": {"id": 4, "name": "qux"}},
+            ],
+        }));
+    }
+
+    #[test]
+    fn array_key_ignores_order() {
+        let obj1 = json!([{"a": 1, "b": 2, "v": true}, {"a": 1, "b": 3, "v": false}]);
+        let obj2 = json!([{"a": 1, "b": 3, "v": false}, {"a": 1, "b": 2, "v": true}]);
+
+        let diff = DiffBuilder::default()
+            .array_keys("[_]", &["a", "b"])
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = diff.compare();
+
+        assert!(diff.is_none(), "diff should be None, but got: {:?}", diff);
+    }
+
+    #[test]
+    fn array_key_matches_keyless_elements_by_position() {
+        let obj = json!([{"name": "a"}, 1, {"id": 1}, "x", null]);
+
+        let diff = DiffBuilder::default()
+            .array_key("[_]", "id")
+            .source(obj.clone()).target(obj).build().unwrap();
+
+        let diff = diff.compare();
+
+        assert!(diff.is_none(), "diff should be None, but got: {:?}", diff);
+
+        let obj1 = json!([{"id": 1}, 1, 2]);
+        let obj2 = json!([2, {"id": 1}, 3]);
+
+        let diff = DiffBuilder::default()
+            .array_key("[_]", "id")
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = serde_json::to_value(diff.compare()).unwrap();
+
+        assert_eq!(diff["edits"], json!([
+            {
+                "edit": "modify",
+                "source_index": 1,
+                "target_index": 0,
+                "value_diff": {"difference_of": "scalar", "source": 1, "target": 2},
+            },
+            {
+                "edit": "modify",
+                "source_index": 2,
+                "target_index": 2,
+                "value_diff": {"difference_of": "scalar", "source": 2, "target": 3},
+            },
+        ]));
+    }
+
+    #[test]
+    fn unordered_arrays() {
+        let obj1 = json!({
+            "tags": ["a", "b", "b", "c"],
+            "users": [
+                {"name": "John", "roles": ["admin", "dev"]},
+                {"name": "Joe", "roles": ["dev"]},
+            ],
+        });
+
+        let obj2 = json!({
+            "tags": ["c", "b", "a", "d"],
+            "users": [
+                {"name": "Joe", "roles": ["dev"]},
+                {"name": "John", "roles": ["dev", "admin", "ops"]},
+            ],
+        });
+
+        let diff = DiffBuilder::default()
+            .array_diff_mode(ArrayDiffMode::Unordered)
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = serde_json::to_value(diff.compare()).unwrap();
+
+        assert_eq!(diff["different_entries"]["tags"]["value_diff"]["edits"], json!([
+            {"edit": "delete", "index": 2, "value": "b"},
+            {"edit": "insert", "index": 3, "value": "d"},
+        ]));
+        assert_eq!(diff["different_entries"]["tags"]["value_diff"]["moved"], json!([[0, 2], [3, 0]]));
+        assert_eq!(diff["different_entries"]["users"]["value_diff"]["edits"], json!([
+            {
+                "edit": "modify",
+                "source_index": 0,
+                "target_index": 1,
+                "value_diff": {
+                    "difference_of": "object",
+                    "different_entries": {
+                        "roles": {
+                            "entry_difference": "value",
+                            "value_diff": {
+                                "difference_of": "array",
+                                "array_difference": "matched",
+                                "edits": [{"edit": "insert", "index": 2, "value": "ops"}],
+                                "moved": [[0, 1], [1, 0]],
+                            },
+                        },
+                    },
+                },
+            },
+        ]));
+    }
+
+    #[test]
+    fn unordered_array_at_path() {
+        let obj1 = json!({"tags": ["a", "b"], "order": [1, 2]});
+        let obj2 = json!({"tags": ["b", "a"], "order": [2, 1]});
+
+        let diff = DiffBuilder::default()
+            .unordered_array("tags")
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = serde_json::to_value(diff.compare()).unwrap();
+
+        assert!(diff["different_entries"].get("tags").is_none(), "tags should be equal, but got: {}", diff);
+        assert!(diff["different_entries"].get("order").is_some(), "order should differ, but got: {}", diff);
+    }
+
+    #[test]
+    fn serializable_values() {
+        #[derive(Serialize)]
+        struct User {
+            name: &'static str,
+            tags: Vec<&'static str>,
+            address: Address,
+        }
+
+        #[derive(Serialize)]
+        struct Address {
+            city: &'static str,
+            updated_at: u64,
+        }
+
+        let john = User { name: "John", tags: vec!["a"], address: Address { city: "Astana", updated_at: 1 } };
+        let joe = User { name: "Joe", tags: vec!["a"], address: Address { city: "Almaty", updated_at: 2 } };
+
+        let diff = diff_serializable(&john, &joe).unwrap().unwrap();
+        assert_eq!(diff.to_string(), "address.city: - \"Astana\" / + \"Almaty\"\naddress.updated_at: - 1 / + 2\nname: - \"John\" / + \"Joe\"\n");
+
+        let diff = DiffBuilder::default()
+            .ignore_path("address.updated_at")
+            .source_serializable(&john)
+            .target(json!({"name": "John", "tags": ["a"], "address": {"city": "Astana", "updated_at": 3}}))
+            .build()
+            .unwrap();
+        assert!(diff.compare().is_none(), "diff should be None, but got: {:?}", diff.compare());
+
+        let keys_not_strings = HashMap::from([((1, 2), 3)]);
+        assert!(matches!(
+            diff_serializable(&keys_not_strings, &json!({})),
+            Err(Error::Serialize { document: "source", .. }),
+        ));
+    }
+
+    #[test]
+    fn compare_modes() {
+        let actual = json!({"name": "John", "extra": 1, "tags": ["c", "a", "b"], "list": [1, 2, 3]});
+        let expected = json!({"name": "John", "tags": ["b", "a"], "list": [1, 2]});
+
+        let diff = DiffBuilder::default()
+            .compare_mode(CompareMode::Contains)
+            .unordered_array("tags")
+            .source(actual.clone()).target(expected.clone()).build().unwrap();
+        assert!(diff.compare().is_none(), "diff should be None, but got: {:?}", diff.compare());
+
+        let diff = DiffBuilder::default()
+            .compare_mode(CompareMode::ContainedIn)
+            .array_diff_mode(ArrayDiffMode::Lcs)
+            .source(expected.clone()).target(actual.clone()).build().unwrap();
+        let diff = serde_json::to_value(diff.compare()).unwrap();
+        assert_eq!(diff["different_entries"].as_object().unwrap().keys().collect::<Vec<_>>(), ["tags"], "got: {}", diff);
+
+        let diff = DiffBuilder::default()
+            .compare_mode(CompareMode::Contains)
+            .source(expected).target(actual).build().unwrap();
+        let diff = serde_json::to_value(diff.compare()).unwrap();
+        assert_eq!(diff["different_entries"].as_object().unwrap().keys().collect::<Vec<_>>(), ["extra", "list", "tags"], "got: {}", diff);
+
+        let contains = |actual, expected| {
+            let diff = DiffBuilder::default()
+                .compare_mode(CompareMode::Contains)
+                .source(actual).target(expected).build().unwrap();
+            diff.compare().map(|diff| diff.to_string())
+        };
+        assert_eq!(contains(json!([1, 2, 3]), json!([2, 3])), None);
+        assert_eq!(contains(json!([1, 2, 3]), json!([1, 3])), None);
+        assert_eq!(contains(json!([{"a": 1, "b": 1}, {"a": 2, "b": 2}]), json!([{"a": 2}])), None);
+        assert_eq!(contains(json!([1, 2, 3]), json!([3, 1])).as_deref(), Some("[1]: + 1\n"));
+    }
+
+    #[test]
+    fn borrowed_and_owned_differences() {
+        let diff = DiffBuilder::default()
+            .source(json!(null)).target(json!(null)).build().unwrap();
+
+        let owned = {
+            let obj1 = json!({"name": "John", "tags": ["a"]});
+            let obj2 = json!({"name": "Joe", "tags": ["a", "b"]});
+            let borrowed = diff.compare_values(&obj1, &obj2).unwrap();
+            assert_eq!(serde_json::to_value(&borrowed).unwrap(), json!({
+                "difference_of": "object",
+                "different_entries": {
+                    "name": {"entry_difference": "value", "value_diff": {"difference_of": "scalar", "source": "John", "target": "Joe"}},
+                    "tags": {"entry_difference": "value", "value_diff": {
+                        "difference_of": "array",
+                        "array_difference": "shorter",
+                        "different_pairs": null,
+                        "missing_elements": ["b"],
+                        "source_length": 1,
+                    }},
+                },
+            }));
+            borrowed.into_owned()
+        };
+
+        assert_eq!(owned.to_string(), "name: - \"John\" / + \"Joe\"\ntags.[1]: + \"b\"\n");
+
+        let (obj1, obj2) = (json!({"a": 1}), json!({"a": 2}));
+        let diff = DiffBuilder::default().source_ref(&obj1).target_ref(&obj2).build().unwrap();
+
+        assert!(std::ptr::eq(diff.source(), &obj1));
+        assert_eq!(diff.compare().unwrap().to_string(), "a: - 1 / + 2\n");
+    }
+
+    #[test]
+    fn build_errors() {
+        let build = |builder: &mut DiffBuilder| builder.source(json!(null)).target(json!(null)).build().map(|_| ());
+
+        assert_eq!(
+            build(DiffBuilder::default().ignore_path("items.[x].id")),
+            Err(Error::InvalidPath {
+                path: "items.[x].id".to_string(),
+                source: PathError { position: 7, message: "Invalid array index: x".to_string() },
+            }),
+        );
+        assert!(matches!(
+            build(DiffBuilder::default().approx_float_eq_epsilon(-1.0)),
+            Err(Error::InvalidOption { option: "approx_float_eq_epsilon", .. }),
+        ));
+        assert!(matches!(
+            build(DiffBuilder::default().number_tolerance_at("a", Tolerance::Relative(-0.1))),
+            Err(Error::InvalidOption { option: "number_tolerance_at", .. }),
+        ));
+        assert!(matches!(
+            build(DiffBuilder::default().number_tolerance_at("a", Tolerance::Ulps(1)).number_tolerance_at("a", Tolerance::Ulps(2))),
+            Err(Error::ConflictingRules { .. }),
+        ));
+        assert!(matches!(
+            build(DiffBuilder::default().date_time_at("at", DateTimeOptions { formats: Vec::new(), ..Default::default() })),
+            Err(Error::InvalidOption { option: "date_time_at", .. }),
+        ));
+        assert!(matches!(
+            build(DiffBuilder::default().date_time_at("on", DateTimeOptions {
+                tolerance: Duration::from_secs(36 * 3600),
+                date_only: true,
+                ..Default::default()
+            })),
+            Err(Error::InvalidOption { option: "date_time_at", .. }),
+        ));
+        assert!(build(DiffBuilder::default().date_time_at("on", DateTimeOptions {
+            tolerance: Duration::from_secs(2 * 86400),
+            date_only: true,
+            ..Default::default()
+        }))
+        .is_ok());
+        assert!(matches!(
+            build(DiffBuilder::default().array_keys("items", &[])),
+            Err(Error::InvalidOption { option: "array_keys", .. }),
+        ));
+        assert!(matches!(
+            build(DiffBuilder::default().array_key("items", "id").unordered_array("items")),
+            Err(Error::ConflictingRules { .. }),
+        ));
+        assert!(matches!(
+            build(DiffBuilder::default().array_key("items", "id").unordered_array("items.[_]")),
+            Err(Error::ConflictingRules { .. }),
+        ));
+        assert!(matches!(
+            build(DiffBuilder::default().array_diff_mode_at("items.[_]", ArrayDiffMode::Lcs).unordered_array("items")),
+            Err(Error::ConflictingRules { .. }),
+        ));
+        assert!(matches!(
+            build(DiffBuilder::default().ignore_path("a").ignore_path_with_missing("a", true)),
+            Err(Error::ConflictingRules { .. }),
+        ));
+        assert_eq!(
+            DiffBuilder::default().source(json!(null)).build().map(|_| ()),
+            Err(Error::UninitializedField("target")),
+        );
+
+        assert!(build(DiffBuilder::default().ignore_path("items.[_].id").ignore_path("items.[0].id")).is_ok());
+    }
+
+    #[test]
+    fn json_pointer_paths() {
+        let path = Path::from_json_pointer("/items/0/a~1b/m~0n/-").unwrap();
+
+        assert_eq!(*path, [
+            PathElement::Key("items".to_string()),
+            PathElement::ArrayIndex(ArrayIndex::Index(0)),
+            PathElement::Key("a/b".to_string()),
+            PathElement::Key("m~n".to_string()),
+            PathElement::Key("-".to_string()),
+        ]);
+        assert_eq!(path.to_json_pointer().as_deref(), Some("/items/0/a~1b/m~0n/-"));
+        assert_eq!(path.to_string(), "items.[0].a/b.m~n.-");
+        assert_eq!(Path::from_json_pointer("").unwrap().to_json_pointer().as_deref(), Some(""));
+        assert_eq!(Path::from_json_pointer("/01").unwrap().to_string(), "01");
+        assert_eq!("a.*.[_]".parse::<Path>().unwrap().to_json_pointer(), None);
+        assert_eq!(Path::from_json_pointer("items"), Err(PathError { position: 0, message: "JSON Pointer must start with `/`".to_string() }));
+        assert_eq!(Path::from_json_pointer("/a/b~2"), Err(PathError { position: 3, message: "Invalid escape in `b~2`".to_string() }));
+
+        let obj1 = json!({"items": [{"name": "a", "id": 1}], "map": {"0": 1, "1": 1}});
+        let obj2 = json!({"items": [{"name": "b", "id": 2}], "map": {"0": 2, "1": 2}});
+
+        let diff = DiffBuilder::default()
+            .ignore_path("/items/0/name")
+            .ignore_path("/map/0")
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), "items.[0].id: - 1 / + 2\nmap.1: - 1 / + 2\n");
+
+        // Dotted indices only match array elements.
+        let diff = DiffBuilder::default()
+            .ignore_path("map.[0]")
+            .source(json!({"map": {"0": 1}})).target(json!({"map": {"0": 2}})).build().unwrap();
+
+        assert_eq!(diff.compare().unwrap().to_string(), "map.0: - 1 / + 2\n");
+    }
+
+    #[test]
+    fn path_display_round_trips() {
+        let keys = ["", "'", "it's", "/a", "$", "$x", "a.b", "[0]", "*", "**", "0", " "];
+        let elements: Vec<_> = keys
+            .iter()
+            .map(|key| PathElement::Key(key.to_string()))
+            .chain([
+                PathElement::ArrayIndex(ArrayIndex::Index(0)),
+                PathElement::ArrayIndex(ArrayIndex::All),
+                PathElement::AnyKey,
+                PathElement::AnyDepth,
+            ])
+            .collect();
+
+        // Every path of one to three of the elements
+        let mut paths: Vec<Vec<PathElement>> = vec![Vec::new()];
+        for _ in 0..3 {
+            let longer: Vec<_> = paths
+                .iter()
+                .filter(|path| path.len() == paths.last().unwrap().len())
+                .flat_map(|path| elements.iter().map(move |element| [&path[..], std::slice::from_ref(element)].concat()))
+                .collect();
+            paths.extend(longer);
+        }
+        paths.remove(0);
+        for path in paths.into_iter().map(Path::from) {
+            assert_eq!(parse_path(&path.to_string()).unwrap(), path, "{path}");
+        }
+    }
+}
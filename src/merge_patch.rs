@@ -0,0 +1,172 @@
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::json_patch::escape_pointer_token;
+use crate::{Difference, EntryDifference, PatchError};
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum MergePatchError {
+    /// Merge patch uses `null` to remove keys, so a key set to `null` can't be expressed.
+    #[error("the null value at `{path}` cannot be represented in a merge patch")]
+    UnrepresentableNull { path: String },
+    #[error(transparent)]
+    Patch(#[from] PatchError),
+}
+
+impl Difference<'_> {
+    /// Converts the difference into an [RFC 7386](https://www.rfc-editor.org/rfc/rfc7386) JSON Merge
+    /// Patch that turns `source` into the compared target.
+    ///
+    /// `source` must be the document the difference was computed from. Changed arrays are replaced
+    /// as a whole. Fails if the target sets an object member to `null`.
+    pub fn to_merge_patch(&self, source: &Value) -> Result<Value, MergePatchError> {
+        self.merge_patch(&mut String::new(), source)
+    }
+
+    fn merge_patch(&self, pointer: &mut String, source: &Value) -> Result<Value, MergePatchError> {
+        let Difference::Object { different_entries } = self else {
+            let target = self.apply(source)?;
+            check_nulls(pointer, &target)?;
+            return Ok(target);
+        };
+
+        let mut patch = Map::new();
+        for (key, entry) in &different_entries.0 {
+            let len = pointer.len();
+            pointer.push('/');
+            pointer.push_str(&escape_pointer_token(key));
+            let value = match entry {
+                EntryDifference::Missing { value } if value.is_null() => {
+                    return Err(MergePatchError::UnrepresentableNull { path: pointer.clone() })
+                }
+                EntryDifference::Missing { value } => {
+                    check_nulls(pointer, value)?;
+                    value.clone().into_owned()
+                }
+                EntryDifference::Extra { .. } => Value::Null,
+                EntryDifference::Value { value_diff } => match value_diff.merge_patch(pointer, &source[key.as_ref()])? {
+                    Value::Null => return Err(MergePatchError::UnrepresentableNull { path: pointer.clone() }),
+                    value => value,
+                },
+            };
+            pointer.truncate(len);
+            patch.insert(key.to_string(), value);
+        }
+
+        Ok(Value::Object(patch))
+    }
+}
+
+/// Merge patches drop `null` members of objects they add, so fail if `value` has any.
+fn check_nulls(pointer: &mut String, value: &Value) -> Result<(), MergePatchError> {
+    let Value::Object(map) = value else {
+        return Ok(());
+    };
+
+    for (key, value) in map {
+        let len = pointer.len();
+        pointer.push('/');
+        pointer.push_str(&escape_pointer_token(key));
+        if value.is_null() {
+            return Err(MergePatchError::UnrepresentableNull { path: pointer.clone() });
+        }
+        check_nulls(pointer, value)?;
+        pointer.truncate(len);
+    }
+
+    Ok(())
+}
+
+/// Applies an [RFC 7386](https://www.rfc-editor.org/rfc/rfc7386) JSON Merge Patch to `source` and
+/// returns the patched document.
+pub fn apply_merge_patch(source: &Value, patch: &Value) -> Value {
+    let Value::Object(patch) = patch else {
+        return patch.clone();
+    };
+
+    let mut target = match source {
+        Value::Object(map) => map.clone(),
+        _ => Map::new(),
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            let merged = apply_merge_patch(target.get(key).unwrap_or(&Value::Null), value);
+            target.insert(key.clone(), merged);
+        }
+    }
+
+    Value::Object(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::DiffBuilder;
+    use super::*;
+
+    #[test]
+    fn test_to_merge_patch() {
+        let source = json!({
+            "title": "Goodbye!",
+            "author": {"givenName": "John", "familyName": "Doe"},
+            "tags": ["example", "sample"],
+            "content": "This will be unchanged",
+        });
+        let target = json!({
+            "title": "Hello!",
+            "author": {"givenName": "John"},
+            "tags": ["example"],
+            "content": "This will be unchanged",
+            "phoneNumber": "+01-123-456-7890",
+        });
+
+        let patch = DiffBuilder::default()
+            .source(source.clone())
+            .target(target.clone())
+            .build()
+            .unwrap()
+            .merge_patch()
+            .unwrap();
+
+        assert_eq!(patch, json!({
+            "title": "Hello!",
+            "author": {"familyName": null},
+            "tags": ["example"],
+            "phoneNumber": "+01-123-456-7890",
+        }));
+        assert_eq!(apply_merge_patch(&source, &patch), target);
+    }
+
+    #[test]
+    fn unrepresentable_nulls() {
+        let merge_patch = |source: Value, target: Value| {
+            DiffBuilder::default().source(source).target(target).build().unwrap().merge_patch()
+        };
+
+        assert_eq!(
+            merge_patch(json!({"a": 1}), json!({"a": null})),
+            Err(MergePatchError::UnrepresentableNull { path: "/a".to_string() }),
+        );
+        assert_eq!(
+            merge_patch(json!({}), json!({"a": {"b": null}})),
+            Err(MergePatchError::UnrepresentableNull { path: "/a/b".to_string() }),
+        );
+        assert_eq!(merge_patch(json!({"a": [1]}), json!({"a": [null]})), Ok(json!({"a": [null]})));
+        assert_eq!(merge_patch(json!({"a": 1}), json!(null)), Ok(json!(null)));
+    }
+
+    #[test]
+    fn test_apply_merge_patch() {
+        assert_eq!(apply_merge_patch(&json!({"a": "b"}), &json!({"a": "c"})), json!({"a": "c"}));
+        assert_eq!(apply_merge_patch(&json!({"a": "b"}), &json!({"b": "c"})), json!({"a": "b", "b": "c"}));
+        assert_eq!(apply_merge_patch(&json!({"a": "b", "b": "c"}), &json!({"a": null})), json!({"b": "c"}));
+        assert_eq!(apply_merge_patch(&json!({"a": [{"b": "c"}]}), &json!({"a": [1]})), json!({"a": [1]}));
+        assert_eq!(apply_merge_patch(&json!(["a", "b"]), &json!(["c", "d"])), json!(["c", "d"]));
+        assert_eq!(apply_merge_patch(&json!({"a": "b"}), &json!(["c"])), json!(["c"]));
+        assert_eq!(apply_merge_patch(&json!({"e": null}), &json!({"a": 1})), json!({"e": null, "a": 1}));
+        assert_eq!(apply_merge_patch(&json!([1, 2]), &json!({"a": "b", "c": null})), json!({"a": "b"}));
+        assert_eq!(apply_merge_patch(&json!({}), &json!({"a": {"bb": {"ccc": null}}})), json!({"a": {"bb": {}}}));
+    }
+}
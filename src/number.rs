@@ -0,0 +1,106 @@
+use serde_json::Number;
+
+/// Whether `source` and `target` have exactly the same value, however they are written,
+/// e.g. `1`, `1.0` and `10e-1`.
+///
+/// With the `arbitrary_precision` feature, numbers are compared as written in the documents, so
+/// integers beyond 64 bits and floats beyond `f64` precision are compared exactly too.
+pub(crate) fn exact_eq(source: &Number, target: &Number) -> bool {
+    if source == target {
+        return true;
+    }
+    if let (Some(source), Some(target)) = (source.as_i128(), target.as_i128()) {
+        return source == target;
+    }
+    let (source, target) = (source.to_string(), target.to_string());
+    match (Decimal::parse(&source), Decimal::parse(&target)) {
+        (Some(source), Some(target)) => source == target,
+        _ => source == target,
+    }
+}
+
+/// Whether `number` is written with a fraction or an exponent.
+pub(crate) fn is_float(number: &Number) -> bool {
+    number.is_f64() || !(number.is_i64() || number.is_u64()) && number.to_string().contains(['.', 'e', 'E'])
+}
+
+/// A JSON number as `digits * 10^exponent`, without leading or trailing zeros in `digits`.
+#[derive(PartialEq, Debug)]
+struct Decimal {
+    negative: bool,
+    digits: String,
+    exponent: i64,
+}
+
+impl Decimal {
+    fn parse(number: &str) -> Option<Self> {
+        let (negative, number) = match number.strip_prefix('-') {
+            Some(number) => (true, number),
+            None => (false, number),
+        };
+        let (mantissa, exponent) = match number.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+            None => (number, 0),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if integer.is_empty() || !integer.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let digits = format!("{integer}{fraction}");
+        let significant = digits.trim_end_matches('0');
+        // Exponents near the limits of `i64` fall back to comparing the numbers as written.
+        let exponent = exponent
+            .checked_sub(i64::try_from(fraction.len()).ok()?)?
+            .checked_add(i64::try_from(digits.len() - significant.len()).ok()?)?;
+        let significant = significant.trim_start_matches('0');
+        Some(match significant.is_empty() {
+            true => Decimal { negative: false, digits: String::new(), exponent: 0 },
+            false => Decimal { negative, digits: significant.to_string(), exponent },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_eq() {
+        let eq = |source: &str, target: &str| {
+            exact_eq(&serde_json::from_str(source).unwrap(), &serde_json::from_str(target).unwrap())
+        };
+
+        assert!(eq("1", "1.0"));
+        assert!(eq("1", "10e-1"));
+        assert!(eq("-0.0", "0"));
+        assert!(eq("1.5e3", "1500"));
+        assert!(eq("18446744073709551615", "18446744073709551615"));
+        assert!(!eq("-1", "18446744073709551615"));
+        assert!(!eq("-1", "1"));
+        assert!(!eq("0.1", "0.10000000000000002"));
+        assert!(!eq("9007199254740993", "9007199254740992.0"));
+        assert_eq!(Decimal::parse("0.1e-9223372036854775808"), None);
+        assert_eq!(Decimal::parse("10e9223372036854775807"), None);
+        assert!(Decimal::parse("1e9223372036854775807").is_some());
+
+        assert!(is_float(&serde_json::from_str("1.0").unwrap()));
+        assert!(!is_float(&serde_json::from_str("-1").unwrap()));
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn test_arbitrary_precision() {
+        let eq = |source: &str, target: &str| {
+            exact_eq(&serde_json::from_str(source).unwrap(), &serde_json::from_str(target).unwrap())
+        };
+
+        assert!(eq("123456789012345678901234567890", "1.2345678901234567890123456789e29"));
+        assert!(!eq("123456789012345678901234567890", "123456789012345678901234567891"));
+        assert!(!eq("0.1", "0.1000000000000000000001"));
+        assert!(is_float(&serde_json::from_str("1e400").unwrap()));
+        assert!(eq("0.1e-9223372036854775808", "0.1e-9223372036854775808"));
+        assert!(!eq("0.1e-9223372036854775808", "0.10e-9223372036854775808"));
+        assert!(!is_float(&serde_json::from_str("123456789012345678901234567890").unwrap()));
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+
+use crate::{index_token, ArrayIndex, Path, PathElement};
+
+/// Matches path patterns against the path being compared, one element at a time.
+///
+/// The patterns are stored in a trie, and the matcher keeps the trie nodes reached by every prefix
+/// of the current path. Stepping into a value only follows the edges of those nodes, so its cost
+/// depends on the wildcards in the patterns instead of on the number of patterns.
+///
+/// When several patterns match, [`PathMatcher::best_match`] picks the most specific one: the one with
+/// the most exact keys and indices, then the most `*` and `[_]` wildcards, which unlike `**` match
+/// exactly one element. Patterns that are equally specific are decided by the one added last.
+#[derive(Debug)]
+pub(crate) struct PathMatcher<T> {
+    nodes: Vec<Node>,
+    values: Vec<T>,
+    /// The specificity of the pattern of each value
+    specificities: Vec<Specificity>,
+    /// The nodes reached by the current path and each of its prefixes
+    stack: Vec<Vec<usize>>,
+    /// How many elements the current path has beyond the last prefix that reached any node
+    unmatched_depth: usize,
+}
+
+/// A path pattern, and whether it was written as a JSON Pointer, whose tokens like `0` match both
+/// array indices and object keys.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Pattern<'p> {
+    elements: &'p [PathElement],
+    from_pointer: bool,
+}
+
+impl Pattern<'_> {
+    /// Whether both patterns consist of exactly the same elements, treating `[_]` only as equal to itself.
+    pub(crate) fn is_same(self, other: Pattern<'_>) -> bool {
+        self.elements.len() == other.elements.len()
+            && self.elements.iter().zip(other.elements).all(|pair| match pair {
+                (PathElement::ArrayIndex(ArrayIndex::All), PathElement::ArrayIndex(ArrayIndex::All)) => true,
+                (PathElement::ArrayIndex(ArrayIndex::All), _) | (_, PathElement::ArrayIndex(ArrayIndex::All)) => false,
+                (a, b) => a == b,
+            })
+    }
+
+    fn specificity(self) -> Specificity {
+        self.elements.iter().fold((0, 0), |(exact, wildcards), element| match element {
+            PathElement::Key(_) | PathElement::ArrayIndex(ArrayIndex::Index(_)) => (exact + 1, wildcards),
+            PathElement::AnyKey | PathElement::ArrayIndex(ArrayIndex::All) => (exact, wildcards + 1),
+            PathElement::AnyDepth => (exact, wildcards),
+        })
+    }
+}
+
+/// The number of exact elements and of single-element wildcards in a pattern, compared in that order.
+type Specificity = (usize, usize);
+
+impl<'p> From<&'p Path> for Pattern<'p> {
+    fn from(path: &'p Path) -> Self {
+        Pattern { elements: path, from_pointer: path.1 }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    keys: HashMap<String, usize>,
+    indices: HashMap<usize, usize>,
+    /// Indices from JSON Pointers, which also match keys named like them
+    pointer_indices: HashMap<usize, usize>,
+    any_key: Option<usize>,
+    any_index: Option<usize>,
+    /// The node of a `**` following this one
+    any_depth: Option<usize>,
+    /// Whether this node is reached by a `**`, and so matches any element again
+    is_any_depth: bool,
+    /// Indices of the values of the patterns ending here
+    values: Vec<usize>,
+}
+
+impl<T> PathMatcher<T> {
+    pub(crate) fn new<'p>(patterns: impl IntoIterator<Item = (Pattern<'p>, T)>) -> Self {
+        let mut matcher = PathMatcher {
+            nodes: vec![Node::default()],
+            values: Vec::new(),
+            specificities: Vec::new(),
+            stack: Vec::new(),
+            unmatched_depth: 0,
+        };
+        for (pattern, value) in patterns {
+            let mut node = 0;
+            for element in pattern.elements {
+                node = matcher.child(node, element, pattern.from_pointer);
+            }
+            matcher.nodes[node].values.push(matcher.values.len());
+            matcher.values.push(value);
+            matcher.specificities.push(pattern.specificity());
+        }
+
+        let mut root = Vec::new();
+        if !matcher.values.is_empty() {
+            matcher.reach(&mut root, 0);
+        }
+        matcher.stack.push(root);
+        matcher
+    }
+
+    /// Steps into the child `element` of the current path.
+    pub(crate) fn push(&mut self, element: &PathElement) {
+        if self.unmatched_depth > 0 || self.stack.last().is_some_and(Vec::is_empty) {
+            self.unmatched_depth += 1;
+            return;
+        }
+
+        let mut next = Vec::new();
+        for &node in self.stack.last().into_iter().flatten() {
+            let node = &self.nodes[node];
+            let children = match element {
+                PathElement::Key(key) => [
+                    node.keys.get(key).copied(),
+                    node.any_key,
+                    index_token(key).and_then(|index| node.pointer_indices.get(&index).copied()),
+                ],
+                PathElement::ArrayIndex(ArrayIndex::Index(index)) => {
+                    [node.indices.get(index).copied(), node.any_index, node.pointer_indices.get(index).copied()]
+                }
+                _ => [None; 3],
+            };
+            for child in children.into_iter().flatten() {
+                self.reach(&mut next, child);
+            }
+        }
+        for &node in self.stack.last().into_iter().flatten() {
+            if self.nodes[node].is_any_depth {
+                self.reach(&mut next, node);
+            }
+        }
+        self.stack.push(next);
+    }
+
+    /// Steps back to the parent of the current path.
+    pub(crate) fn pop(&mut self) {
+        match self.unmatched_depth {
+            0 => drop(self.stack.pop()),
+            _ => self.unmatched_depth -= 1,
+        }
+    }
+
+    /// The values of the patterns that match the current path, in no particular order.
+    pub(crate) fn matches(&self) -> impl Iterator<Item = &T> {
+        self.matching().map(|value| &self.values[value])
+    }
+
+    /// The value of the most specific pattern that matches the current path, or of the one added
+    /// last among equally specific patterns.
+    pub(crate) fn best_match(&self) -> Option<&T> {
+        self.matching()
+            .max_by_key(|&value| (self.specificities[value], value))
+            .map(|value| &self.values[value])
+    }
+
+    fn matching(&self) -> impl Iterator<Item = usize> + '_ {
+        self.stack
+            .last()
+            .filter(|_| self.unmatched_depth == 0)
+            .into_iter()
+            .flatten()
+            .flat_map(|&node| self.nodes[node].values.iter().copied())
+    }
+
+    /// Adds `node` to `nodes`, along with the `**` nodes that can follow it without consuming an element.
+    fn reach(&self, nodes: &mut Vec<usize>, mut node: usize) {
+        loop {
+            if nodes.contains(&node) {
+                return;
+            }
+            nodes.push(node);
+            match self.nodes[node].any_depth {
+                Some(any_depth) => node = any_depth,
+                None => return,
+            }
+        }
+    }
+
+    fn child(&mut self, node: usize, element: &PathElement, from_pointer: bool) -> usize {
+        let next = self.nodes.len();
+        let is_any_depth = matches!(element, PathElement::AnyDepth);
+        let node = &mut self.nodes[node];
+        let child = match element {
+            PathElement::Key(key) => *node.keys.entry(key.clone()).or_insert(next),
+            PathElement::AnyKey => *node.any_key.get_or_insert(next),
+            PathElement::ArrayIndex(ArrayIndex::Index(index)) if from_pointer => *node.pointer_indices.entry(*index).or_insert(next),
+            PathElement::ArrayIndex(ArrayIndex::Index(index)) => *node.indices.entry(*index).or_insert(next),
+            PathElement::ArrayIndex(ArrayIndex::All) => *node.any_index.get_or_insert(next),
+            PathElement::AnyDepth => *node.any_depth.get_or_insert(next),
+        };
+        if child == next {
+            self.nodes.push(Node { is_any_depth, ..Node::default() });
+        }
+        child
+    }
+}
+
+/// The path of the array that a rule given for `path` applies to, which may name either the array
+/// or, with a trailing `[_]`, its elements.
+pub(crate) fn array_path(path: &Path) -> Pattern<'_> {
+    match path.split_last() {
+        Some((PathElement::ArrayIndex(ArrayIndex::All), parent)) => Pattern { elements: parent, from_pointer: path.1 },
+        _ => path.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(patterns: &[&str], path: &[PathElement]) -> Vec<usize> {
+        let patterns: Vec<Path> = patterns
+            .iter()
+            .map(|pattern| match pattern.starts_with('/') {
+                true => Path::from_json_pointer(pattern).unwrap(),
+                false => pattern.parse().unwrap(),
+            })
+            .collect();
+        let mut matcher = PathMatcher::new(patterns.iter().enumerate().map(|(i, pattern)| (pattern.into(), i)));
+        for element in path {
+            matcher.push(element);
+        }
+        let mut matches: Vec<_> = matcher.matches().copied().collect();
+        matches.sort_unstable();
+        matches
+    }
+
+    #[test]
+    fn test_path_matcher() {
+        let key = |key: &str| PathElement::Key(key.to_string());
+        let index = |index| PathElement::ArrayIndex(ArrayIndex::Index(index));
+        let patterns = ["a.b", "a.*", "**.updated_at", "users.[*].id", "users.[1].id", "**", "a.**.c.**.d"];
+
+        assert_eq!(matches(&patterns, &[key("a"), key("b")]), [0, 1, 5]);
+        assert_eq!(matches(&patterns, &[key("updated_at")]), [2, 5]);
+        assert_eq!(matches(&patterns, &[key("x"), index(3), key("updated_at")]), [2, 5]);
+        assert_eq!(matches(&patterns, &[key("users"), index(0), key("id")]), [3, 5]);
+        assert_eq!(matches(&patterns, &[key("users"), index(1), key("id")]), [3, 4, 5]);
+        assert_eq!(matches(&patterns, &[key("a"), index(1)]), [5]);
+        assert_eq!(matches(&patterns, &[key("a"), key("c"), key("d")]), [5, 6]);
+        assert_eq!(matches(&patterns, &[key("a"), key("x"), key("c"), index(0), key("d")]), [5, 6]);
+        assert_eq!(matches(&patterns, &[]), [5]);
+        assert_eq!(matches(&["a"], &[key("a"), key("b")]), Vec::<usize>::new());
+
+        // Only JSON Pointers can't tell indices from keys named like them.
+        let patterns = ["a.[0]", "/a/0", "a.0"];
+        assert_eq!(matches(&patterns, &[key("a"), index(0)]), [0, 1]);
+        assert_eq!(matches(&patterns, &[key("a"), key("0")]), [1, 2]);
+    }
+
+    #[test]
+    fn best_match() {
+        let best = |patterns: &[&str], path: &[PathElement]| {
+            let patterns: Vec<Path> = patterns.iter().map(|pattern| pattern.parse().unwrap()).collect();
+            let mut matcher = PathMatcher::new(patterns.iter().enumerate().map(|(i, pattern)| (pattern.into(), i)));
+            for element in path {
+                matcher.push(element);
+            }
+            matcher.best_match().copied()
+        };
+        let key = |key: &str| PathElement::Key(key.to_string());
+        let index = |index| PathElement::ArrayIndex(ArrayIndex::Index(index));
+        let path = [key("prices"), index(0), key("amount")];
+
+        assert_eq!(best(&["prices.[_].amount", "prices.[0].amount"], &path), Some(1));
+        assert_eq!(best(&["prices.[0].amount", "prices.[_].amount"], &path), Some(0));
+        assert_eq!(best(&["**.amount", "prices.*"], &[key("prices"), key("amount")]), Some(1));
+        assert_eq!(best(&["prices.*", "**.amount"], &[key("prices"), key("amount")]), Some(0));
+        assert_eq!(best(&["**", "prices.**", "prices.[_].*"], &path), Some(2));
+        assert_eq!(best(&["x"], &path), None);
+
+        // Equally specific patterns, like a JSON Pointer and the same dotted path, go to the one added last.
+        let pointer = Path::from_json_pointer("/prices/0/amount").unwrap();
+        let dotted: Path = "prices.[0].amount".parse().unwrap();
+        for (patterns, expected) in [([&pointer, &dotted], 1), ([&dotted, &pointer], 1)] {
+            let mut matcher = PathMatcher::new(patterns.into_iter().enumerate().map(|(i, pattern)| (pattern.into(), i)));
+            for element in &path {
+                matcher.push(element);
+            }
+            assert_eq!(matcher.best_match(), Some(&expected));
+        }
+    }
+}
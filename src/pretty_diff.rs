@@ -0,0 +1,411 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::render::{GREEN, RED, RESET};
+use crate::{ArrayDifference, ArrayEdit, ColorChoice, Difference, DumbMap, EntryDifference};
+
+const INDENT: &str = "  ";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum LineKind {
+    Context,
+    Removed,
+    Added,
+}
+
+#[derive(Debug)]
+struct Line {
+    kind: LineKind,
+    text: String,
+}
+
+impl Difference<'_> {
+    /// Renders the difference as a unified diff of the pretty-printed `source` and target, with
+    /// `context` unchanged lines around each change.
+    ///
+    /// `source` must be the document the difference was computed from. Lines come from the
+    /// structural difference, so ignored paths and reordered keys don't show up as changes.
+    pub fn unified_diff(&self, source: &Value, context: usize, color: ColorChoice) -> String {
+        let lines = annotated_lines(self, source);
+        let color = color.enabled();
+
+        // Line numbers of `source` and target before each line.
+        let mut numbers = Vec::with_capacity(lines.len() + 1);
+        let (mut s, mut t) = (1, 1);
+        for line in &lines {
+            numbers.push((s, t));
+            s += usize::from(line.kind != LineKind::Added);
+            t += usize::from(line.kind != LineKind::Removed);
+        }
+        numbers.push((s, t));
+
+        let mut out = String::from("--- source\n+++ target\n");
+        let changed: Vec<_> = (0..lines.len()).filter(|&i| lines[i].kind != LineKind::Context).collect();
+        let mut hunk_start = 0;
+        while hunk_start < changed.len() {
+            // Changes closer than twice the context end up in the same hunk.
+            let mut hunk_end = hunk_start;
+            while hunk_end + 1 < changed.len() && changed[hunk_end + 1] - changed[hunk_end] <= 2 * context + 1 {
+                hunk_end += 1;
+            }
+            let from = changed[hunk_start].saturating_sub(context);
+            let to = (changed[hunk_end] + context + 1).min(lines.len());
+
+            let ((s_from, t_from), (s_to, t_to)) = (numbers[from], numbers[to]);
+            out.push_str(&format!("@@ -{},{} +{},{} @@\n", s_from, s_to - s_from, t_from, t_to - t_from));
+            for line in &lines[from..to] {
+                let (marker, line_color) = match line.kind {
+                    LineKind::Context => (' ', None),
+                    LineKind::Removed => ('-', Some(RED)),
+                    LineKind::Added => ('+', Some(GREEN)),
+                };
+                match line_color.filter(|_| color) {
+                    Some(line_color) => out.push_str(&format!("{line_color}{marker}{}{RESET}\n", line.text)),
+                    None => out.push_str(&format!("{marker}{}\n", line.text)),
+                }
+            }
+            hunk_start = hunk_end + 1;
+        }
+
+        out
+    }
+
+    /// Renders the difference as the pretty-printed `source` and target side by side, each column
+    /// `width` characters wide.
+    ///
+    /// `source` must be the document the difference was computed from. Changed lines are marked
+    /// with `|`, removed lines with `<` and added lines with `>`.
+    pub fn side_by_side_diff(&self, source: &Value, width: usize, color: ColorChoice) -> String {
+        let lines = annotated_lines(self, source);
+        let color = color.enabled();
+
+        let mut out = String::new();
+        let mut push_row = |left: Option<&str>, marker: char, right: Option<&str>| {
+            let left = fit(left.unwrap_or_default(), width);
+            let right = right.map(|right| fit(right, width)).unwrap_or_default();
+            let (left_color, right_color) = match (color, marker) {
+                (true, '<') => (RED, ""),
+                (true, '>') => ("", GREEN),
+                (true, '|') => (RED, GREEN),
+                _ => ("", ""),
+            };
+            let paint = |text: String, code: &str| match code {
+                "" => text,
+                code => format!("{code}{text}{RESET}"),
+            };
+            let row = format!("{} {marker} {}", paint(left, left_color), paint(right, right_color));
+            out.push_str(row.trim_end());
+            out.push('\n');
+        };
+
+        let mut i = 0;
+        while i < lines.len() {
+            if lines[i].kind == LineKind::Context {
+                push_row(Some(&lines[i].text), ' ', Some(&lines[i].text));
+                i += 1;
+                continue;
+            }
+
+            // Pair a run of removed lines with the added lines that follow it.
+            let removed_end = (i..lines.len()).find(|&j| lines[j].kind != LineKind::Removed).unwrap_or(lines.len());
+            let added_end = (removed_end..lines.len()).find(|&j| lines[j].kind != LineKind::Added).unwrap_or(lines.len());
+            let (removed, added) = (&lines[i..removed_end], &lines[removed_end..added_end]);
+            for k in 0..removed.len().max(added.len()) {
+                let (left, right) = (removed.get(k), added.get(k));
+                let marker = match (left, right) {
+                    (Some(_), Some(_)) => '|',
+                    (Some(_), None) => '<',
+                    _ => '>',
+                };
+                push_row(left.map(|line| line.text.as_str()), marker, right.map(|line| line.text.as_str()));
+            }
+            i = added_end;
+        }
+
+        out
+    }
+}
+
+/// Pads or truncates `text` to exactly `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    match text.chars().count() {
+        len if len > width => text.chars().take(width.saturating_sub(1)).chain(['…']).collect(),
+        len => format!("{text}{}", " ".repeat(width - len)),
+    }
+}
+
+/// The lines of `source` pretty-printed, with the changes of `diff` in place. Unlike
+/// [`walk`](crate::walk), this follows `source` too, as the unchanged values are printed around the
+/// changes.
+fn annotated_lines(diff: &Difference, source: &Value) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut printer = Printer { lines: &mut lines };
+    printer.node(source, Some(diff), 0, None, Commas::default());
+    lines
+}
+
+/// Whether a line is followed by a comma in `source` and in target.
+#[derive(Default, Clone, Copy)]
+struct Commas {
+    source: bool,
+    target: bool,
+}
+
+/// An element of an object or array, and on which side it's present.
+struct Item<'a> {
+    key: Option<&'a str>,
+    source: Option<&'a Value>,
+    target: Option<&'a Value>,
+    diff: Option<&'a Difference<'a>>,
+}
+
+struct Printer<'l> {
+    lines: &'l mut Vec<Line>,
+}
+
+impl Printer<'_> {
+    fn node(&mut self, source: &Value, diff: Option<&Difference>, indent: usize, key: Option<&str>, commas: Commas) {
+        let Some(diff) = diff else {
+            return self.context(source, indent, key, commas);
+        };
+
+        let items = match diff {
+            Difference::Scalar(diff) => {
+                self.value(LineKind::Removed, source, indent, key, commas.source);
+                return self.value(LineKind::Added, &diff.target_value(), indent, key, commas.target);
+            }
+            Difference::Type { target_value, .. } | Difference::Custom { target_value, .. } => {
+                self.value(LineKind::Removed, source, indent, key, commas.source);
+                return self.value(LineKind::Added, target_value, indent, key, commas.target);
+            }
+            Difference::Object { different_entries } => {
+                let entries: HashMap<_, _> = different_entries.0.iter().map(|(key, entry)| (key.as_ref(), entry)).collect();
+                let mut items: Vec<_> = source
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(key, value)| match entries.get(key.as_str()) {
+                        Some(EntryDifference::Extra { .. }) => Item { key: Some(key), source: Some(value), target: None, diff: None },
+                        Some(EntryDifference::Value { value_diff }) => Item { key: Some(key), source: Some(value), target: Some(value), diff: Some(value_diff) },
+                        _ => Item { key: Some(key), source: Some(value), target: Some(value), diff: None },
+                    })
+                    .collect();
+                items.extend(different_entries.0.iter().filter_map(|(key, entry)| match entry {
+                    EntryDifference::Missing { value } => Some(Item { key: Some(key), source: None, target: Some(value), diff: None }),
+                    _ => None,
+                }));
+                items
+            }
+            Difference::Array(diff) => array_items(source.as_array().map_or(&[], Vec::as_slice), diff),
+        };
+
+        let (open, close) = if source.is_array() { ("[", "]") } else { ("{", "}") };
+        self.push(LineKind::Context, indent, key, open);
+        for (i, item) in items.iter().enumerate() {
+            let commas = Commas {
+                source: items[i + 1..].iter().any(|item| item.source.is_some()),
+                target: items[i + 1..].iter().any(|item| item.target.is_some()),
+            };
+            match (item.source, item.target) {
+                (Some(source), Some(_)) => self.node(source, item.diff, indent + 1, item.key, commas),
+                (Some(source), None) => self.value(LineKind::Removed, source, indent + 1, item.key, commas.source),
+                (None, Some(target)) => self.value(LineKind::Added, target, indent + 1, item.key, commas.target),
+                (None, None) => {}
+            }
+        }
+        self.context_last(indent, close, commas);
+    }
+
+    /// An unchanged value, whose last line may still differ by its trailing comma.
+    fn context(&mut self, value: &Value, indent: usize, key: Option<&str>, commas: Commas) {
+        let text = pretty(value, indent, key);
+        let (init, last) = text.rsplit_once('\n').map_or(("", text.as_str()), |(init, last)| (init, last));
+        for line in init.lines() {
+            self.lines.push(Line { kind: LineKind::Context, text: line.to_string() });
+        }
+        self.context_last(0, last, commas);
+    }
+
+    fn context_last(&mut self, indent: usize, text: &str, commas: Commas) {
+        if commas.source == commas.target {
+            return self.push_with_comma(LineKind::Context, indent, text, commas.source);
+        }
+        self.push_with_comma(LineKind::Removed, indent, text, commas.source);
+        self.push_with_comma(LineKind::Added, indent, text, commas.target);
+    }
+
+    fn value(&mut self, kind: LineKind, value: &Value, indent: usize, key: Option<&str>, comma: bool) {
+        let text = pretty(value, indent, key);
+        let mut lines = text.lines().peekable();
+        while let Some(line) = lines.next() {
+            self.push_with_comma(kind, 0, line, comma && lines.peek().is_none());
+        }
+    }
+
+    fn push(&mut self, kind: LineKind, indent: usize, key: Option<&str>, text: &str) {
+        let key = key.map(|key| format!("{}: ", Value::from(key))).unwrap_or_default();
+        self.lines.push(Line { kind, text: format!("{}{key}{text}", INDENT.repeat(indent)) });
+    }
+
+    fn push_with_comma(&mut self, kind: LineKind, indent: usize, text: &str, comma: bool) {
+        let comma = if comma { "," } else { "" };
+        self.lines.push(Line { kind, text: format!("{}{text}{comma}", INDENT.repeat(indent)) });
+    }
+}
+
+fn array_items<'a>(source: &'a [Value], diff: &'a ArrayDifference<'a>) -> Vec<Item<'a>> {
+    let both = |value, diff| Item { key: None, source: Some(value), target: Some(value), diff };
+    let removed = |value| Item { key: None, source: Some(value), target: None, diff: None };
+    let added = |value| Item { key: None, source: None, target: Some(value), diff: None };
+
+    let positional = |pairs: Option<&'a DumbMap<usize, Difference<'a>>>, kept: usize, missing: &'a [Cow<'a, Value>]| {
+        let pairs: HashMap<_, _> = pairs.into_iter().flat_map(|pairs| &pairs.0).map(|(i, diff)| (*i, diff)).collect();
+        source
+            .iter()
+            .enumerate()
+            .map(|(i, value)| match i < kept {
+                true => both(value, pairs.get(&i).copied()),
+                false => removed(value),
+            })
+            .chain(missing.iter().map(|value| added(value)))
+            .collect()
+    };
+
+    match diff {
+        ArrayDifference::PairsOnly { different_pairs } => positional(Some(different_pairs), source.len(), &[]),
+        ArrayDifference::Shorter { different_pairs, missing_elements, .. } => {
+            positional(different_pairs.as_ref(), source.len(), missing_elements)
+        }
+        ArrayDifference::Longer { different_pairs, target_length, .. } => positional(different_pairs.as_ref(), *target_length, &[]),
+        ArrayDifference::EditScript { edits } => {
+            let mut items = Vec::new();
+            let (mut cursor, mut shift) = (0, 0isize);
+            for edit in edits {
+                let until = match edit {
+                    ArrayEdit::Insert { index, .. } => index.saturating_add_signed(-shift),
+                    ArrayEdit::Delete { index, .. } => *index,
+                    ArrayEdit::Modify { source_index, .. } => *source_index,
+                };
+                items.extend(source[cursor..until.max(cursor)].iter().map(|value| both(value, None)));
+                cursor = cursor.max(until);
+                match edit {
+                    ArrayEdit::Insert { value, .. } => {
+                        items.push(added(value));
+                        shift += 1;
+                    }
+                    ArrayEdit::Delete { .. } => {
+                        items.push(removed(&source[cursor]));
+                        cursor += 1;
+                        shift -= 1;
+                    }
+                    ArrayEdit::Modify { value_diff, .. } => {
+                        items.push(both(&source[cursor], Some(value_diff)));
+                        cursor += 1;
+                    }
+                }
+            }
+            items.extend(source[cursor..].iter().map(|value| both(value, None)));
+            items
+        }
+        ArrayDifference::Matched { edits, .. } => {
+            let mut changed = HashMap::new();
+            for edit in edits {
+                match edit {
+                    ArrayEdit::Delete { index, .. } => changed.insert(*index, None),
+                    ArrayEdit::Modify { source_index, value_diff, .. } => changed.insert(*source_index, Some(value_diff)),
+                    ArrayEdit::Insert { .. } => None,
+                };
+            }
+            let mut items: Vec<_> = source.iter().enumerate().map(|(i, value)| match changed.get(&i) {
+                Some(None) => removed(value),
+                Some(Some(diff)) => both(value, Some(*diff)),
+                None => both(value, None),
+            }).collect();
+            items.extend(edits.iter().filter_map(|edit| match edit {
+                ArrayEdit::Insert { value, .. } => Some(added(value)),
+                _ => None,
+            }));
+            items
+        }
+    }
+}
+
+/// Pretty-prints `value` indented by `indent` levels, prefixed with its `key`.
+fn pretty(value: &Value, indent: usize, key: Option<&str>) -> String {
+    let prefix = INDENT.repeat(indent);
+    let key = key.map(|key| format!("{}: ", Value::from(key))).unwrap_or_default();
+    let text = serde_json::to_string_pretty(value).unwrap_or_default();
+    let text = text.replace('\n', &format!("\n{prefix}"));
+    format!("{prefix}{key}{text}")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{ArrayDiffMode, DiffBuilder};
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let source = json!({
+            "name": "John",
+            "updated_at": 1,
+            "tags": ["a", "b", "c"],
+            "address": {"city": "Astana", "zip": 123},
+            "removed": true,
+        });
+        let target = json!({
+            "name": "Joe",
+            "updated_at": 2,
+            "tags": ["x", "a", "c"],
+            "address": {"city": "Astana", "zip": 123},
+        });
+
+        let diff = DiffBuilder::default()
+            .ignore_path("updated_at")
+            .array_diff_mode(ArrayDiffMode::Lcs)
+            .source(source.clone())
+            .target(target)
+            .build()
+            .unwrap();
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.unified_diff(&source, 1, ColorChoice::Never), r#"--- source
++++ target
+@@ -5,7 +5,6 @@
+   },
+-  "name": "John",
++  "name": "Joe",
+-  "removed": true,
+   "tags": [
++    "x",
+     "a",
+-    "b",
+     "c"
+"#);
+    }
+
+    #[test]
+    fn test_side_by_side_diff() {
+        let source = json!({"a": 1, "b": [1, 2]});
+        let target = json!({"a": 2, "b": [1]});
+
+        let diff = DiffBuilder::default()
+            .source(source.clone())
+            .target(target)
+            .build()
+            .unwrap();
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.side_by_side_diff(&source, 10, ColorChoice::Never), r#"{            {
+  "a": 1,  |   "a": 2,
+  "b": [       "b": [
+    1,     |     1
+    2      <
+  ]            ]
+}            }
+"#);
+    }
+}
@@ -0,0 +1,322 @@
+use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
+use std::io::IsTerminal;
+
+use serde_json::Value;
+
+use crate::datetime::seconds;
+use crate::visitor::{walk, DifferenceVisitor};
+use crate::{Difference, Path, ScalarDifference, TextEdit, Tolerance};
+
+pub(crate) const RED: &str = "\x1b[31m";
+pub(crate) const GREEN: &str = "\x1b[32m";
+pub(crate) const RESET: &str = "\x1b[0m";
+
+/// Whether rendered output is colored with ANSI escape codes.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorChoice {
+    /// Colored if stdout is a terminal and `NO_COLOR` isn't set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub(crate) fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+impl Difference<'_> {
+    /// Renders the difference as a human-readable report with one `path: - old / + new` line per change.
+    pub fn render(&self, color: ColorChoice) -> String {
+        let mut renderer = Renderer {
+            out: String::new(),
+            color: color.enabled(),
+        };
+        walk(self, &mut renderer);
+        renderer.out
+    }
+}
+
+impl Display for Difference<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(ColorChoice::Never))
+    }
+}
+
+impl ScalarDifference<'_> {
+    /// Explains why the values differ when a tolerance decided it, e.g. `off by 3.2s, tolerance 1s`.
+    pub fn reason(&self) -> Option<String> {
+        match self {
+            ScalarDifference::ApproxNumber { delta, relative_error, tolerance, tolerance_path, .. } => {
+                let tolerance = match tolerance {
+                    Tolerance::Absolute(epsilon) => significant(*epsilon, 6),
+                    Tolerance::Relative(max_relative) => format!("{}%", significant(max_relative * 100.0, 6)),
+                    Tolerance::Ulps(max_ulps) => format!("{max_ulps} ulps"),
+                };
+                let (delta, relative_error) = (significant(delta.abs(), 6), significant(relative_error * 100.0, 3));
+                let rule = tolerance_path.as_ref().map(|path| format!(" for `{path}`")).unwrap_or_default();
+                Some(format!("off by {delta} or {relative_error}%, tolerance {tolerance}{rule}"))
+            }
+            ScalarDifference::DateTime { delta, tolerance, date_only: true, .. } => {
+                let days = |days: u64| if days == 1 { "1 day".to_string() } else { format!("{days} days") };
+                Some(format!("off by {}, tolerance {}", days(delta.num_days().unsigned_abs()), days(tolerance.as_secs() / 86400)))
+            }
+            ScalarDifference::DateTime { delta, tolerance, .. } => {
+                let (delta, tolerance) = (significant(seconds(*delta).abs(), 6), significant(tolerance.as_secs_f64(), 6));
+                Some(format!("off by {delta}s, tolerance {tolerance}s"))
+            }
+            ScalarDifference::Bool { .. } | ScalarDifference::String { .. } | ScalarDifference::Number { .. } => None,
+        }
+    }
+}
+
+/// Formats `value` with at most `digits` significant digits.
+fn significant(value: f64, digits: i32) -> String {
+    if value == 0.0 || !value.is_finite() {
+        return value.to_string();
+    }
+    let decimals = (digits - 1 - value.abs().log10().floor() as i32).max(0) as usize;
+    let formatted = format!("{value:.decimals$}");
+    match formatted.contains('.') {
+        true => formatted.trim_end_matches('0').trim_end_matches('.').to_string(),
+        false => formatted,
+    }
+}
+
+struct Renderer {
+    out: String,
+    color: bool,
+}
+
+impl DifferenceVisitor<'_> for Renderer {
+    fn added(&mut self, path: &Path, value: &Value) {
+        self.line(path, None, Some(value), None);
+    }
+
+    fn removed(&mut self, path: &Path, value: &Value) {
+        self.line(path, Some(value), None, None);
+    }
+
+    fn scalar_changed(&mut self, path: &Path, diff: &ScalarDifference<'_>) {
+        if let ScalarDifference::String { text_diff: Some(edits), .. } = diff {
+            return self.text_diff(path, edits);
+        }
+        self.line(path, Some(&diff.source_value()), Some(&diff.target_value()), diff.reason());
+    }
+
+    fn type_changed(&mut self, path: &Path, source: &Value, target: &Value) {
+        self.line(path, Some(source), Some(target), None);
+    }
+
+    fn custom_changed(&mut self, path: &Path, source: &Value, target: &Value, comparator: Option<&str>) {
+        let reason = match comparator {
+            Some(comparator) => format!("different by the comparator for `{comparator}`"),
+            None => "different by a comparator".to_string(),
+        };
+        self.line(path, Some(source), Some(target), Some(reason));
+    }
+
+    fn extra_elements(&mut self, path: &Path, indices: Range<usize>) {
+        let count = indices.len();
+        let plural = if count == 1 { "" } else { "s" };
+        self.path_prefix(path);
+        self.colored(RED, &format!("- {count} extra element{plural}"));
+        self.out.push('\n');
+    }
+}
+
+impl Renderer {
+    fn line(&mut self, path: &Path, source: Option<&Value>, target: Option<&Value>, reason: Option<String>) {
+        self.path_prefix(path);
+        if let Some(source) = source {
+            self.colored(RED, &format!("- {source}"));
+        }
+        if source.is_some() && target.is_some() {
+            self.out.push_str(" / ");
+        }
+        if let Some(target) = target {
+            self.colored(GREEN, &format!("+ {target}"));
+        }
+        if let Some(reason) = reason {
+            self.out.push_str(&format!(" ({reason})"));
+        }
+        self.out.push('\n');
+    }
+
+    /// Writes a changed string once, as `~ "text"` with `[-deleted-]` and `{+inserted+}` runs.
+    fn text_diff(&mut self, path: &Path, edits: &[TextEdit<'_>]) {
+        // The text of a JSON string, without the quotes.
+        let escape = |text: &str| {
+            let quoted = Value::from(text).to_string();
+            quoted[1..quoted.len() - 1].to_string()
+        };
+        self.path_prefix(path);
+        self.out.push_str("~ \"");
+        for edit in edits {
+            match edit {
+                TextEdit::Equal(text) => self.out.push_str(&escape(text)),
+                TextEdit::Insert(text) => self.colored(GREEN, &format!("{{+{}+}}", escape(text))),
+                TextEdit::Delete(text) => self.colored(RED, &format!("[-{}-]", escape(text))),
+            }
+        }
+        self.out.push_str("\"\n");
+    }
+
+    fn path_prefix(&mut self, path: &Path) {
+        match path.is_empty() {
+            true => self.out.push_str("(root)"),
+            false => self.out.push_str(&path.to_string()),
+        }
+        self.out.push_str(": ");
+    }
+
+    fn colored(&mut self, color: &str, text: &str) {
+        match self.color {
+            true => self.out.extend([color, text, RESET]),
+            false => self.out.push_str(text),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::time::Duration;
+
+    use chrono::TimeDelta;
+    use serde_json::{json, Number};
+    use crate::{ArrayDiffMode, DiffBuilder, TextGranularity};
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let source = json!({
+            "user": {"name": "John", "a.b": true},
+            "removed": 1,
+            "tags": ["a", "b", "c"],
+            "list": [1, 2, 3],
+            "type": "1",
+        });
+        let target = json!({
+            "user": {"name": "Joe", "a.b": false},
+            "added": {"x": 1},
+            "tags": ["x", "a", "c"],
+            "list": [1, 5],
+            "type": 1,
+        });
+
+        let diff = DiffBuilder::default()
+            .array_diff_mode_at("tags", ArrayDiffMode::Lcs)
+            .source(source)
+            .target(target)
+            .build()
+            .unwrap();
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), [
+            r#"list.[1]: - 2 / + 5"#,
+            r#"list: - 1 extra element"#,
+            r#"removed: - 1"#,
+            r#"tags.[0]: + "x""#,
+            r#"tags.[1]: - "b""#,
+            r#"type: - "1" / + 1"#,
+            r#"user.'a.b': - true / + false"#,
+            r#"user.name: - "John" / + "Joe""#,
+            r#"added: + {"x":1}"#,
+            "",
+        ].join("\n"));
+
+        assert_eq!(
+            diff.render(ColorChoice::Always).lines().next().unwrap(),
+            "list.[1]: \x1b[31m- 2\x1b[0m / \x1b[32m+ 5\x1b[0m",
+        );
+    }
+
+    #[test]
+    fn test_render_text_diff() {
+        let source = json!({"sql": "SELECT id, name FROM users WHERE active = true", "short": "abc"});
+        let target = json!({"sql": "SELECT id, email FROM users WHERE active = false", "short": "abd"});
+
+        let diff = DiffBuilder::default()
+            .text_diff(TextGranularity::Words)
+            .source(source)
+            .target(target)
+            .build()
+            .unwrap();
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), [
+            r#"short: - "abc" / + "abd""#,
+            r#"sql: ~ "SELECT id, [-name-]{+email+} FROM users WHERE active = [-true-]{+false+}""#,
+            "",
+        ].join("\n"));
+
+        let diff = DiffBuilder::default()
+            .text_diff(TextGranularity::Lines)
+            .text_diff_min_length(0)
+            .source(json!("a\nb\n"))
+            .target(json!("a\nc\n"))
+            .build()
+            .unwrap();
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.render(ColorChoice::Always), "(root): ~ \"a\\n\x1b[31m[-b\\n-]\x1b[0m\x1b[32m{+c\\n+}\x1b[0m\"\n");
+        assert_eq!(serde_json::to_value(&diff).unwrap()["text_diff"], json!([
+            {"op": "equal", "text": "a\n"},
+            {"op": "delete", "text": "b\n"},
+            {"op": "insert", "text": "c\n"},
+        ]));
+
+        // Strings in arrays are diffed once their elements are paired up.
+        let diff = DiffBuilder::default()
+            .text_diff(TextGranularity::Words)
+            .text_diff_min_length(0)
+            .array_key("items", "id")
+            .source(json!({"items": [{"id": 1, "name": "old name"}, {"id": 2}]}))
+            .target(json!({"items": [{"id": 2}, {"id": 1, "name": "new name"}]}))
+            .build()
+            .unwrap();
+        let diff = diff.compare().unwrap();
+
+        assert_eq!(diff.to_string(), "items.[0].name: ~ \"[-old-]{+new+} name\"\n");
+    }
+
+    #[test]
+    fn test_reasons() {
+        let number = |source: f64, target: f64, tolerance, tolerance_path: Option<&str>| ScalarDifference::ApproxNumber {
+            source: Number::from_f64(source).unwrap(),
+            target: Number::from_f64(target).unwrap(),
+            delta: target - source,
+            relative_error: (target - source).abs() / source.abs().max(target.abs()),
+            tolerance,
+            tolerance_path: tolerance_path.map(str::to_string),
+        };
+        let date_time = |delta, tolerance, date_only| ScalarDifference::DateTime {
+            source: Cow::Owned(json!("a")),
+            target: Cow::Owned(json!("b")),
+            delta,
+            tolerance,
+            date_only,
+        };
+
+        assert_eq!(number(2.0, 1.5, Tolerance::Relative(0.1), None).reason().unwrap(), "off by 0.5 or 25%, tolerance 10%");
+        assert_eq!(number(2.0, 1.5, Tolerance::Absolute(0.1), Some("a.*")).reason().unwrap(), "off by 0.5 or 25%, tolerance 0.1 for `a.*`");
+        assert_eq!(number(1.0, 1.0000002, Tolerance::Ulps(4), None).reason().unwrap(), "off by 0.0000002 or 0.00002%, tolerance 4 ulps");
+        assert_eq!(
+            date_time(TimeDelta::milliseconds(-3200), Duration::from_secs(1), false).reason().unwrap(),
+            "off by 3.2s, tolerance 1s",
+        );
+        assert_eq!(
+            date_time(TimeDelta::days(2), Duration::from_secs(86400), true).reason().unwrap(),
+            "off by 2 days, tolerance 1 day",
+        );
+        assert_eq!(ScalarDifference::Bool { source: true, target: false }.reason(), None);
+    }
+}
@@ -0,0 +1,182 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::lcs::longest_common_subsequence;
+use crate::{ArrayDifference, ArrayEdit, Difference, EntryDifference, ScalarDifference};
+
+/// The units two strings are diffed in, see [`DiffBuilder::text_diff`](crate::DiffBuilder::text_diff).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextGranularity {
+    /// Single characters.
+    Chars,
+    /// Runs of letters, digits and underscores, runs of whitespace, and single other characters.
+    Words,
+    /// Lines, including their line breaks.
+    Lines,
+}
+
+/// A run of text in an inline diff of two strings.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(tag = "op", content = "text", rename_all = "snake_case")]
+pub enum TextEdit<'a> {
+    /// Text that both strings have
+    Equal(Cow<'a, str>),
+    /// Text that only `target` has
+    Insert(Cow<'a, str>),
+    /// Text that only `source` has
+    Delete(Cow<'a, str>),
+}
+
+impl TextEdit<'_> {
+    pub fn into_owned(self) -> TextEdit<'static> {
+        match self {
+            TextEdit::Equal(text) => TextEdit::Equal(Cow::Owned(text.into_owned())),
+            TextEdit::Insert(text) => TextEdit::Insert(Cow::Owned(text.into_owned())),
+            TextEdit::Delete(text) => TextEdit::Delete(Cow::Owned(text.into_owned())),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// The edits that turn `source` into `target`, with deletions before insertions, or `None` if
+/// the strings have too many tokens.
+pub(crate) fn text_diff<'a>(source: &'a str, target: &'a str, granularity: TextGranularity) -> Option<Vec<TextEdit<'a>>> {
+    let source_tokens = tokens(source, granularity);
+    let target_tokens = tokens(target, granularity);
+    let pairs = longest_common_subsequence(source_tokens.len(), target_tokens.len(), |i, j| {
+        source[source_tokens[i].clone()] == target[target_tokens[j].clone()]
+    })?;
+
+    let mut runs: Vec<(Op, Range<usize>)> = Vec::new();
+    let mut push = |op: Op, token: &Range<usize>| match runs.last_mut() {
+        Some((last, run)) if *last == op => run.end = token.end,
+        _ => runs.push((op, token.clone())),
+    };
+    let (mut i, mut j) = (0, 0);
+    for (next_i, next_j) in pairs.into_iter().chain([(source_tokens.len(), target_tokens.len())]) {
+        source_tokens[i..next_i].iter().for_each(|token| push(Op::Delete, token));
+        target_tokens[j..next_j].iter().for_each(|token| push(Op::Insert, token));
+        if let Some(token) = source_tokens.get(next_i) {
+            push(Op::Equal, token);
+        }
+        (i, j) = (next_i + 1, next_j + 1);
+    }
+
+    Some(runs
+        .into_iter()
+        .map(|(op, run)| match op {
+            Op::Equal => TextEdit::Equal(Cow::Borrowed(&source[run])),
+            Op::Insert => TextEdit::Insert(Cow::Borrowed(&target[run])),
+            Op::Delete => TextEdit::Delete(Cow::Borrowed(&source[run])),
+        })
+        .collect())
+}
+
+impl<'a> Difference<'a> {
+    /// Adds an inline diff to each changed string with at least `min_length` characters in either value.
+    pub(crate) fn add_text_diffs(&mut self, granularity: TextGranularity, min_length: usize) {
+        match self {
+            Difference::Scalar(ScalarDifference::String { source, target, text_diff: edits }) => {
+                if source.chars().count().max(target.chars().count()) < min_length {
+                    return;
+                }
+                *edits = match (&*source, &*target) {
+                    (Cow::Borrowed(source), Cow::Borrowed(target)) => text_diff(source, target, granularity),
+                    (source, target) => {
+                        text_diff(source, target, granularity).map(|edits| edits.into_iter().map(TextEdit::into_owned).collect())
+                    }
+                };
+            }
+            Difference::Scalar(_) | Difference::Type { .. } | Difference::Custom { .. } => {}
+            Difference::Object { different_entries } => {
+                for (_, entry) in &mut different_entries.0 {
+                    if let EntryDifference::Value { value_diff } = entry {
+                        value_diff.add_text_diffs(granularity, min_length);
+                    }
+                }
+            }
+            Difference::Array(diff) => {
+                let pairs = match diff {
+                    ArrayDifference::PairsOnly { different_pairs } => Some(different_pairs),
+                    ArrayDifference::Shorter { different_pairs, .. } | ArrayDifference::Longer { different_pairs, .. } => {
+                        different_pairs.as_mut()
+                    }
+                    ArrayDifference::EditScript { edits } | ArrayDifference::Matched { edits, .. } => {
+                        for edit in edits {
+                            if let ArrayEdit::Modify { value_diff, .. } = edit {
+                                value_diff.add_text_diffs(granularity, min_length);
+                            }
+                        }
+                        None
+                    }
+                };
+                for (_, diff) in pairs.into_iter().flat_map(|pairs| &mut pairs.0) {
+                    diff.add_text_diffs(granularity, min_length);
+                }
+            }
+        }
+    }
+}
+
+/// Splits `text` into the byte ranges of its tokens.
+fn tokens(text: &str, granularity: TextGranularity) -> Vec<Range<usize>> {
+    let class = |c: char| match c {
+        c if c.is_alphanumeric() || c == '_' => 0,
+        c if c.is_whitespace() => 1,
+        _ => 2,
+    };
+    let mut previous = None;
+    let mut starts: Vec<usize> = text
+        .char_indices()
+        .filter(|&(_, c)| match (granularity, previous.replace(c)) {
+            (_, None) | (TextGranularity::Chars, _) => true,
+            (TextGranularity::Words, Some(previous)) => class(previous) != class(c) || class(c) == 2,
+            (TextGranularity::Lines, Some(previous)) => previous == '\n',
+        })
+        .map(|(i, _)| i)
+        .collect();
+    starts.push(text.len());
+    starts.windows(2).map(|bounds| bounds[0]..bounds[1]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Renders the diff with `[-deleted-]` and `{+inserted+}` markers.
+    fn diff(source: &str, target: &str, granularity: TextGranularity) -> String {
+        text_diff(source, target, granularity)
+            .unwrap()
+            .into_iter()
+            .map(|edit| match edit {
+                TextEdit::Equal(text) => text.into_owned(),
+                TextEdit::Insert(text) => format!("{{+{text}+}}"),
+                TextEdit::Delete(text) => format!("[-{text}-]"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_text_diff() {
+        assert_eq!(diff("kitten", "sitting", TextGranularity::Chars), "[-k-]{+s+}itt[-e-]{+i+}n{+g+}");
+        assert_eq!(
+            diff("The quick brown fox.", "The quick red fox!", TextGranularity::Words),
+            "The quick [-brown-]{+red+} fox[-.-]{+!+}",
+        );
+        assert_eq!(
+            diff("SELECT a\nFROM t\nWHERE x = 1\n", "SELECT a\nFROM u\nWHERE x = 1", TextGranularity::Lines),
+            "SELECT a\n[-FROM t\nWHERE x = 1\n-]{+FROM u\nWHERE x = 1+}",
+        );
+        assert_eq!(diff("", "añb", TextGranularity::Chars), "{+añb+}");
+        assert_eq!(diff("same", "same", TextGranularity::Words), "same");
+        assert!(text_diff(&"a".repeat(3000), &"b".repeat(3000), TextGranularity::Chars).is_none());
+    }
+}
@@ -0,0 +1,141 @@
+use std::ops::Range;
+
+use serde_json::Value;
+
+use crate::{ArrayDifference, ArrayEdit, ArrayIndex, Difference, EntryDifference, Path, PathElement, ScalarDifference};
+
+/// Callbacks for the changes in a [`Difference`], called by [`walk`] with the absolute path of
+/// each change. All of them do nothing by default.
+pub trait DifferenceVisitor<'d> {
+    /// `target` has `value` at `path`, and `source` doesn't.
+    fn added(&mut self, _path: &Path, _value: &'d Value) {}
+
+    /// `source` has `value` at `path`, and `target` doesn't.
+    fn removed(&mut self, _path: &Path, _value: &'d Value) {}
+
+    /// The scalar at `path` changed its value.
+    fn scalar_changed(&mut self, _path: &Path, _diff: &'d ScalarDifference<'_>) {}
+
+    /// The value at `path` changed its type.
+    fn type_changed(&mut self, _path: &Path, _source: &'d Value, _target: &'d Value) {}
+
+    /// A [`Comparator`](crate::Comparator) found the values at `path` different as a whole.
+    /// `comparator` is the path it was registered for, if any.
+    fn custom_changed(&mut self, _path: &Path, _source: &'d Value, _target: &'d Value, _comparator: Option<&'d str>) {}
+
+    /// `source` has elements at `indices`, past the end of the array at `path` in `target`.
+    fn extra_elements(&mut self, _path: &Path, _indices: Range<usize>) {}
+}
+
+/// Walks `diff` and calls `visitor` for each change, in the order they are rendered.
+pub fn walk<'d>(diff: &'d Difference<'_>, visitor: &mut impl DifferenceVisitor<'d>) {
+    Walker { path: Path::default(), visitor }.difference(diff);
+}
+
+struct Walker<'v, V> {
+    path: Path,
+    visitor: &'v mut V,
+}
+
+impl<'d, V: DifferenceVisitor<'d>> Walker<'_, V> {
+    fn difference(&mut self, diff: &'d Difference<'_>) {
+        match diff {
+            Difference::Scalar(diff) => self.visitor.scalar_changed(&self.path, diff),
+            Difference::Type { source_value, target_value, .. } => {
+                self.visitor.type_changed(&self.path, source_value, target_value)
+            }
+            Difference::Custom { source_value, target_value, comparator } => {
+                self.visitor.custom_changed(&self.path, source_value, target_value, comparator.as_deref())
+            }
+            Difference::Object { different_entries } => {
+                for (key, entry) in &different_entries.0 {
+                    self.path.push(PathElement::Key(key.to_string()));
+                    match entry {
+                        EntryDifference::Missing { value } => self.visitor.added(&self.path, value),
+                        EntryDifference::Extra { value } => self.visitor.removed(&self.path, value),
+                        EntryDifference::Value { value_diff } => self.difference(value_diff),
+                    }
+                    self.path.pop();
+                }
+            }
+            Difference::Array(diff) => self.array(diff),
+        }
+    }
+
+    fn array(&mut self, diff: &'d ArrayDifference<'_>) {
+        let different_pairs = match diff {
+            ArrayDifference::PairsOnly { different_pairs } => Some(different_pairs),
+            ArrayDifference::Shorter { different_pairs, .. } | ArrayDifference::Longer { different_pairs, .. } => {
+                different_pairs.as_ref()
+            }
+            ArrayDifference::EditScript { edits } | ArrayDifference::Matched { edits, .. } => {
+                for edit in edits {
+                    match edit {
+                        ArrayEdit::Insert { index, value } => self.element(*index, |w| w.visitor.added(&w.path, value)),
+                        ArrayEdit::Delete { index, value } => self.element(*index, |w| w.visitor.removed(&w.path, value)),
+                        ArrayEdit::Modify { source_index, value_diff, .. } => {
+                            self.element(*source_index, |w| w.difference(value_diff))
+                        }
+                    }
+                }
+                None
+            }
+        };
+        for (index, diff) in different_pairs.iter().flat_map(|pairs| &pairs.0) {
+            self.element(*index, |w| w.difference(diff));
+        }
+
+        match diff {
+            ArrayDifference::Shorter { missing_elements, source_length, .. } => {
+                for (index, value) in (*source_length..).zip(missing_elements) {
+                    self.element(index, |w| w.visitor.added(&w.path, value));
+                }
+            }
+            ArrayDifference::Longer { extra_length, target_length, .. } => {
+                self.visitor.extra_elements(&self.path, *target_length..target_length + extra_length)
+            }
+            _ => {}
+        }
+    }
+
+    fn element(&mut self, index: usize, f: impl FnOnce(&mut Self)) {
+        self.path.push(PathElement::ArrayIndex(ArrayIndex::Index(index)));
+        f(self);
+        self.path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::DiffBuilder;
+    use super::*;
+
+    /// Collects one line per added value and changed scalar.
+    #[derive(Default)]
+    struct Collector(Vec<String>);
+
+    impl DifferenceVisitor<'_> for Collector {
+        fn added(&mut self, path: &Path, value: &Value) {
+            self.0.push(format!("{path} added {value}"));
+        }
+
+        fn scalar_changed(&mut self, path: &Path, _diff: &ScalarDifference<'_>) {
+            self.0.push(format!("{path} changed"));
+        }
+    }
+
+    #[test]
+    fn test_walk() {
+        let source = json!({"a": {"x": 1, "y": 2}, "b": [1], "c": 1});
+        let target = json!({"a": {"x": 2, "y": 3, "z": 4}, "b": [2, 3], "c": "1"});
+
+        let diff = DiffBuilder::default().source(source).target(target).build().unwrap();
+        let diff = diff.compare().unwrap();
+
+        let mut collector = Collector::default();
+        walk(&diff, &mut collector);
+
+        assert_eq!(collector.0, ["a.x changed", "a.y changed", "a.z added 4", "b.[0] changed", "b.[1] added 3"]);
+    }
+}